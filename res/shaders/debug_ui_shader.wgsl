@@ -9,11 +9,15 @@ var<uniform> camera: CameraUniform;
 struct VertexInput {
     [[location(0)]] position: vec3<f32>;
     [[location(1)]] color: vec4<f32>;
+    [[location(2)]] rect: vec4<f32>;
+    [[location(3)]] corner_radius: f32;
 };
 
 struct VertexOutput {
     [[builtin(position)]] clip_position: vec4<f32>;
     [[location(0)]] color: vec4<f32>;
+    [[location(1)]] rect: vec4<f32>;
+    [[location(2)]] corner_radius: f32;
 };
 
 [[stage(vertex)]]
@@ -22,11 +26,22 @@ fn main(vertex: VertexInput) -> VertexOutput {
 
     out.clip_position = camera.view_proj * vec4<f32>(vertex.position, 1.0);
     out.color = vertex.color;
+    out.rect = vertex.rect;
+    out.corner_radius = vertex.corner_radius;
 
     return out;
 }
 
 [[stage(fragment)]]
 fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
-    return in.color;
+    var color = in.color;
+
+    if (in.corner_radius > 0.0) {
+        // Signed distance to the rounded rect's edge, fade out over the last pixel.
+        let q = abs(in.rect.xy) - in.rect.zw + vec2<f32>(in.corner_radius);
+        let dist = length(max(q, vec2<f32>(0.0))) + min(max(q.x, q.y), 0.0) - in.corner_radius;
+        color.a = color.a * clamp(0.5 - dist, 0.0, 1.0);
+    }
+
+    return color;
 }
@@ -6,25 +6,43 @@ struct CameraUniform {
 [[group(0), binding(0)]]
 var<uniform> camera: CameraUniform;
 
+[[block]]
+struct GlobalUniform {
+    time_s: f32;
+    wind: vec2<f32>;
+    tint: vec4<f32>;
+};
+[[group(2), binding(0)]]
+var<uniform> globals: GlobalUniform;
+
 struct VertexInput {
     [[location(0)]] position: vec3<f32>;
     [[location(1)]] tex_coords: vec2<f32>;
     [[location(2)]] tex_index: i32;
+    [[location(3)]] tint: vec4<f32>;
+    [[location(4)]] sway: f32;
 };
 
 struct VertexOutput {
     [[builtin(position)]] clip_position: vec4<f32>;
     [[location(0)]] uv: vec2<f32>;
     [[location(1)]] tex_index: i32;
+    [[location(2)]] tint: vec4<f32>;
 };
 
 [[stage(vertex)]]
 fn main(vertex: VertexInput) -> VertexOutput {
     var out: VertexOutput;
 
-    out.clip_position = camera.view_proj * vec4<f32>(vertex.position, 1.0);
+    // Sprite tops lean with the wind while their bases stay planted, phase varies with x so neighbours don't move in lockstep.
+    let gust = sin(globals.time_s * 1.7 + vertex.position.x * 0.6) * 0.5 + 0.5;
+    let lean = gust * vertex.sway * (1.0 - vertex.tex_coords.y);
+    let position = vec3<f32>(vertex.position.xy + globals.wind * lean, vertex.position.z);
+
+    out.clip_position = camera.view_proj * vec4<f32>(position, 1.0);
     out.uv = vertex.tex_coords;
     out.tex_index = vertex.tex_index;
+    out.tint = vertex.tint;
 
     return out;
 }
@@ -32,12 +50,25 @@ fn main(vertex: VertexInput) -> VertexOutput {
 [[group(1), binding(0)]] var texture: texture_2d_array<f32>;
 [[group(1), binding(1)]] var t_samlper: sampler;
 
+// Darkest at the center fading out to the quad's edge, in uv.
+fn shadow_color(uv: vec2<f32>) -> vec4<f32> {
+    let max_shade = 0.5; // Only blocking _some_ of the light...
+    let min_rad = 0.1; // The shadow has a bias at the center.
+    let max_rad = 1.0;
+
+    // Double the distance, because the radius of the quad in uv is 0.5.
+    let t = distance(vec2<f32>(0.5, 0.5), uv) * 2.0;
+    return vec4<f32>(0.0, 0.0, 0.0, max_shade * smoothStep(max_rad, min_rad, t));
+}
+
 [[stage(fragment)]]
 fn main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
-    let texture_index: i32 = in.tex_index;
+    // tex_index -1 (SHADOW_TEX_INDEX) marks a shadow, sampled anyway so the texture is read in uniform control flow.
+    let texture_index: i32 = max(in.tex_index, 0);
     var color = textureSample(texture, t_samlper, in.uv, texture_index);
+    if (in.tex_index < 0) { color = shadow_color(in.uv); }
 
     if (color.a == 0.0) { discard; }
 
-    return color;
+    return color * in.tint * globals.tint;
 }
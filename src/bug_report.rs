@@ -0,0 +1,73 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::Result;
+
+use tree_sprites::game::game_state::GameState;
+use tree_sprites::render::RenderState;
+
+/// Saves frame as <dir>/capture_<timestamp>.png next to a .txt with what it takes to reproduce it. Returns the png's path.
+pub fn write(dir: &Path, frame: &image::RgbaImage, game_state: &GameState, render_state: &RenderState) -> Result<PathBuf> {
+    let report = report(game_state, &render_state.adapter_info(), render_state.surface_format(), render_state.present_mode());
+    save(dir, frame, &report)
+}
+
+fn report(game_state: &GameState, adapter_info: &wgpu::AdapterInfo, surface_format: wgpu::TextureFormat, present_mode: wgpu::PresentMode) -> String {
+    format!(
+        "seed: {}\ntick: {}\nchecksum: {:016x}\ngrid_dim: {}\nsim_speed: {}\n\nadapter: {} ({:?}, {:?})\nsurface format: {:?}\npresent mode: {:?}\n\n{:#?}\n",
+        game_state.seed(),
+        game_state.tick_count,
+        game_state.checksum(),
+        game_state.grid_dim(),
+        game_state.sim_speed,
+        adapter_info.name, adapter_info.backend, adapter_info.device_type,
+        surface_format,
+        present_mode,
+        game_state.debug,
+    )
+}
+
+fn save(dir: &Path, frame: &image::RgbaImage, report: &str) -> Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let name = format!("capture_{}_{:03}", timestamp.as_secs(), timestamp.subsec_millis());
+
+    let image_path = dir.join(&name).with_extension("png");
+    frame.save(&image_path)?;
+    std::fs::write(dir.join(&name).with_extension("txt"), report)?;
+
+    Ok(image_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_writes_the_image_and_its_report() {
+        let game_state = GameState::new_seeded(7);
+        let adapter_info = wgpu::AdapterInfo {
+            name: String::from("Test Adapter"),
+            vendor: 0,
+            device: 0,
+            device_type: wgpu::DeviceType::Cpu,
+            backend: wgpu::Backend::Vulkan,
+        };
+        let report = report(&game_state, &adapter_info, wgpu::TextureFormat::Bgra8UnormSrgb, wgpu::PresentMode::Fifo);
+        assert!(report.contains("seed: 7\n"));
+        assert!(report.contains(&format!("checksum: {:016x}\n", game_state.checksum())));
+        assert!(report.contains("adapter: Test Adapter (Vulkan, Cpu)\n"));
+        assert!(report.contains("present mode: Fifo\n"));
+
+        let dir = std::env::temp_dir().join(format!("tree_sprites_capture_test_{}", std::process::id()));
+        let frame = image::RgbaImage::from_pixel(4, 2, image::Rgba([10, 20, 30, 255]));
+        let image_path = save(&dir, &frame, &report).unwrap();
+
+        let saved = image::open(&image_path).unwrap().to_rgba8();
+        assert_eq!(saved, frame);
+        assert_eq!(std::fs::read_to_string(image_path.with_extension("txt")).unwrap(), report);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
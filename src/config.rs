@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use log::warn;
+
+const DEFAULT_UPS_TARGET: u64 = 120;
+const DEFAULT_FPS_TARGET: u64 = 120;
+const DEFAULT_CAPTURE_DIR: &str = "captures";
+// How far one press of the UPS keys moves the target.
+pub const UPS_NUDGE: i64 = 10;
+
+pub struct SimConfig {
+    pub ups_target: u64,
+    pub fps_target: u64,
+    // Where F12 bug report captures are written.
+    pub capture_dir: PathBuf,
+}
+
+impl SimConfig {
+    /// Defaults, overridden by the UPS_TARGET, FPS_TARGET and CAPTURE_DIR environment variables when set.
+    pub fn from_env() -> Self {
+        Self {
+            ups_target: read_rate_from_env("UPS_TARGET", DEFAULT_UPS_TARGET),
+            fps_target: read_rate_from_env("FPS_TARGET", DEFAULT_FPS_TARGET),
+            capture_dir: std::env::var_os("CAPTURE_DIR").map_or_else(|| PathBuf::from(DEFAULT_CAPTURE_DIR), PathBuf::from),
+        }
+    }
+
+    /// Never below 1 UPS.
+    pub fn nudge_ups_target(&mut self, delta: i64) {
+        self.ups_target = (self.ups_target as i64 + delta).max(1) as u64;
+    }
+
+    pub fn update_target_dt(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / (self.ups_target as f32))
+    }
+
+    pub fn frame_target_dt(&self) -> Duration {
+        Duration::from_secs_f32(1.0 / (self.fps_target as f32))
+    }
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            ups_target: DEFAULT_UPS_TARGET,
+            fps_target: DEFAULT_FPS_TARGET,
+            capture_dir: PathBuf::from(DEFAULT_CAPTURE_DIR),
+        }
+    }
+}
+
+fn read_rate_from_env(key: &str, default: u64) -> u64 {
+    match std::env::var(key) {
+        Ok(value) => match value.parse::<u64>() {
+            Ok(rate) if rate > 0 => rate,
+            _ => {
+                warn!("Ignoring invalid {}={:?}, using {}.", key, value, default);
+                default
+            },
+        },
+        Err(_) => default,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_dts_follow_the_configured_rates() {
+        let mut config = SimConfig { ups_target: 60, fps_target: 144, ..SimConfig::default() };
+
+        assert_eq!(config.update_target_dt(), Duration::from_secs_f32(1.0 / 60.0));
+        assert_eq!(config.frame_target_dt(), Duration::from_secs_f32(1.0 / 144.0));
+
+        config.nudge_ups_target(-100);
+        assert_eq!(config.ups_target, 1);
+        assert_eq!(config.update_target_dt(), Duration::from_secs(1));
+    }
+}
@@ -15,6 +15,12 @@ pub struct DebugTimers {
     pub tree_render_timer: AverageDurationTimer<600>,
 }
 
+impl Default for DebugTimers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DebugTimers {
     pub fn new() -> Self {
         Self {
@@ -0,0 +1,40 @@
+use super::position::WorldPosition;
+use super::trees::TreeSpecies;
+
+/// Notable things that happened to trees during an update.
+#[derive(Copy, Clone, Debug)]
+pub enum SimEvent {
+    Planted { position: WorldPosition, species: TreeSpecies },
+    Killed { position: WorldPosition, species: TreeSpecies },
+    Removed { position: WorldPosition, species: TreeSpecies },
+}
+
+pub trait EventListener {
+    fn on_event(&mut self, event: &SimEvent);
+}
+
+#[derive(Default)]
+pub struct EventListeners(Vec<Box<dyn EventListener>>);
+
+impl EventListeners {
+    pub fn push(&mut self, listener: Box<dyn EventListener>) {
+        self.0.push(listener);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn notify(&mut self, event: &SimEvent) {
+        for listener in self.0.iter_mut() {
+            listener.on_event(event);
+        }
+    }
+}
+
+// NOTE: Listeners are attached to one simulation, a branched (cloned) simulation starts without any.
+impl Clone for EventListeners {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
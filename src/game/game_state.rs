@@ -3,28 +3,51 @@
 use std::time::Duration;
 use std::mem::MaybeUninit;
 
-use rand::Rng;
-use rand::rngs::ThreadRng;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
 
 use crate::timer::{AverageDurationTimer, TargetTimer};
 use crate::timer::measure;
 
+use super::events::{EventListener, EventListeners, SimEvent};
 use super::position::{WorldPosition, TileOffset, TileCoordinate};
-use super::trees::{Tree, TreeGrowthStage, TreeSpecies};
+use super::scenario::{Scenario, ScenarioError, ScenarioTerrain};
+use super::trees::{SpeciesConfig, Tree, TreeGrowthStage, TreeSpecies};
 use super::tree_region_iterator::{TreeRegionIterator, TreeRegionIteratorMut};
 
+// Default grid, see GameState::with_dimensions for others.
 pub const GRID_DIM: usize = 30;
 pub const GRID_SIZE: usize = GRID_DIM * GRID_DIM;
+// Biggest grid accepted from saves and scenarios, ~26M tree slots.
+pub const MAX_GRID_DIM: usize = 1024;
 
 pub const TILE_DIM: f32 = 1.0;
 pub const TILE_RAD: f32 = TILE_DIM * 0.5;
 
 pub const NUM_TREES_PER_TILE: usize = 10;
-const MAX_NUM_TREES: usize = GRID_SIZE * NUM_TREES_PER_TILE;
+
+// Camera zoom limits as visible world height, bigger grids can zoom out further, see GameState::max_zoom.
+pub const MIN_ZOOM: f32 = 1.5;
+pub const MAX_ZOOM: f32 = 30.0;
+
+// Darkest the scene gets at midnight, see GameState::ambient_light.
+pub const MIN_AMBIENT_LIGHT: f32 = 0.2;
+
+// Full dirt <-> grass transitions per second.
+const GRASS_TRANSITION_RATE: f32 = 0.5;
+
+// Growth is scaled by this when a tree isn't on its preferred soil.
+const SOIL_MISMATCH_GROWTH_MULTIPLIER: f32 = 0.4;
+// Living trees whose combined shade and soil growth multiplier falls to this die.
+const MIN_GROWTH_MULTIPLIER: f32 = 0.05;
+// Seconds a tree burns before it dies.
+const BURN_DURATION_S: f32 = 6.0;
+// Chance per second a burning tree ignites each living tree within its shadow radius.
+const FIRE_SPREAD_RATE: f32 = 0.15;
 
 macro_rules! tile_index {
-    ($x:expr, $y:expr) => {
-        $x as usize + ($y as usize * GRID_DIM)
+    ($x:expr, $y:expr, $grid_dim:expr) => {
+        $x as usize + ($y as usize * $grid_dim)
     }
 }
 
@@ -34,10 +57,16 @@ macro_rules! tree_slot_index {
     }
 }
 
+macro_rules! tile_index_of_slot {
+    ($slot:expr) => {
+        $slot / NUM_TREES_PER_TILE
+    }
+}
+
 macro_rules! tree_slot_index_xyt {
-    ($x:expr, $y:expr, $t:expr) => {
+    ($x:expr, $y:expr, $t:expr, $grid_dim:expr) => {
 
-        tree_slot_index!(tile_index!($x, $y), $t)
+        tree_slot_index!(tile_index!($x, $y, $grid_dim), $t)
     }
 }
 
@@ -50,93 +79,449 @@ fn smoothstep(min: f32, max: f32, mut x: f32) -> f32 {
     x * x * x * (x * (x * 6.0 - 15.0) + 10.0)
 }
 
+/// coord if that tile has room for another tree, otherwise the first neighbor on the grid that does.
+fn tile_with_room(per_tile_tree_count: &[u8], grid_dim: usize, coord: TileCoordinate) -> Option<TileCoordinate> {
+    const SEARCH_ORDER: [(i32, i32); 9] = [(0, 0), (-1, 0), (1, 0), (0, -1), (0, 1), (-1, -1), (1, -1), (-1, 1), (1, 1)];
+
+    SEARCH_ORDER.iter()
+        .map(|&(dx, dy)| TileCoordinate { x: coord.x + dx, y: coord.y + dy })
+        .filter(|c| c.x >= 0 && c.x < grid_dim as i32 && c.y >= 0 && c.y < grid_dim as i32)
+        .find(|c| (*per_tile_tree_count.get(tile_index!(c.x, c.y, grid_dim)).unwrap() as usize) < NUM_TREES_PER_TILE)
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GameCamera {
     pub position: cgmath::Point3<f32>,
     pub zoom_level: f32,
 }
 
+/// How the camera frames the world when a simulation starts, zoom_level is the visible world height.
+#[derive(Clone, Copy, Debug)]
+pub enum CameraFraming {
+    CenterOnGrid { zoom_level: f32 },
+    // Centered, zoomed out just far enough to see the whole grid at the given width / height.
+    FitToGrid { aspect_ratio: f32 },
+    Explicit { position: cgmath::Point2<f32>, zoom_level: f32 },
+}
+
+impl Default for CameraFraming {
+    fn default() -> Self {
+        Self::CenterOnGrid { zoom_level: 20.0 }
+    }
+}
+
+impl GameCamera {
+    const Z: f32 = -0.5;
+
+    pub fn framed(framing: CameraFraming, grid_dim: usize) -> Self {
+        let grid_extent = grid_dim as f32 * TILE_DIM;
+        let grid_center = grid_extent * 0.5;
+
+        match framing {
+            CameraFraming::CenterOnGrid { zoom_level } => Self {
+                position: cgmath::Point3::new(grid_center, grid_center, Self::Z),
+                zoom_level,
+            },
+            CameraFraming::FitToGrid { aspect_ratio } => Self {
+                position: cgmath::Point3::new(grid_center, grid_center, Self::Z),
+                zoom_level: f32::max(grid_extent, grid_extent / aspect_ratio),
+            },
+            CameraFraming::Explicit { position, zoom_level } => Self {
+                position: cgmath::Point3::new(position.x, position.y, Self::Z),
+                zoom_level,
+            },
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GroundCover {
     Grass,
     Dirt,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SoilType {
     Stony,
     Normal,
 }
 
+#[derive(Clone, Copy, Debug)]
+pub struct SlowMotion {
+    pub remaining: Duration,
+    pub scale: f32,
+}
+
+/// Slow the sim down for a while when a single tick kills off at least min_kills trees.
+#[derive(Clone, Copy, Debug)]
+pub struct SlowMotionTrigger {
+    pub min_kills: usize,
+    pub duration: Duration,
+    pub scale: f32,
+}
+
+impl Default for SlowMotionTrigger {
+    fn default() -> Self {
+        Self {
+            min_kills: 5,
+            duration: Duration::from_secs(3),
+            scale: 0.1,
+        }
+    }
+}
+
+/// Trees within distance tiles of the grid's edge grow multiplier times as fast (or slow, below 1.0).
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeGrowth {
+    pub distance: f32,
+    pub multiplier: f32,
+}
+
+impl EdgeGrowth {
+    fn multiplier_at(&self, pos: WorldPosition, grid_dim: usize) -> f32 {
+        let x = pos.coord.x as f32 + pos.offset.x;
+        let y = pos.coord.y as f32 + pos.offset.y;
+        let extent = grid_dim as f32;
+
+        let edge_distance = x.min(y).min(extent - x).min(extent - y);
+        if edge_distance <= self.distance { self.multiplier } else { 1.0 }
+    }
+}
+
+/// What happens to a seed that lands off the grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgePolicy {
+    // Lost, stands thin out towards the edges.
+    Drop,
+    // Mirrored back across the edge it crossed, edges end up more crowded.
+    Reflect,
+    // Comes in over the opposite edge, as if the grid were a torus.
+    Wrap,
+}
+
+impl EdgePolicy {
+    /// Where a seed landing at pos takes root, None if it's lost.
+    fn apply(self, pos: WorldPosition, grid_dim: usize) -> Option<WorldPosition> {
+        let in_bounds = |pos: WorldPosition| {
+            pos.coord.x >= 0 && pos.coord.x < grid_dim as i32 && pos.coord.y >= 0 && pos.coord.y < grid_dim as i32
+        };
+
+        if in_bounds(pos) { return Some(pos); }
+
+        let extent = grid_dim as f32;
+        let x = pos.coord.x as f32 + pos.offset.x;
+        let y = pos.coord.y as f32 + pos.offset.y;
+
+        let reflect = |v: f32| {
+            if v < 0.0 {
+                -v
+            } else if v >= extent {
+                (2.0 * extent) - v
+            } else {
+                v
+            }
+        };
+
+        let (x, y) = match self {
+            Self::Drop    => return None,
+            Self::Reflect => (reflect(x), reflect(y)),
+            Self::Wrap    => (x.rem_euclid(extent), y.rem_euclid(extent)),
+        };
+
+        // Still off the grid if the seed flew further than the grid is wide, or rounding landed it exactly on the edge.
+        let result = WorldPosition { coord: TileCoordinate { x: 0, y: 0 }, offset: TileOffset { x, y } }.normalize();
+        if in_bounds(result) { Some(result) } else { None }
+    }
+}
+
+/// The tunable simulation rules a GameState starts with, see the matching fields on GameState.
+/// Default is what GameState::new uses.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SimParams {
+    pub growth_scale: f32,
+    pub max_living_trees: Option<usize>,
+    pub random_tree_flip: bool,
+    pub min_tree_spacing: f32,
+    pub seed_timer_jitter: f32,
+    pub edge_growth: Option<EdgeGrowth>,
+    pub edge_policy: EdgePolicy,
+    pub day_length: Option<Duration>,
+    pub season_length: Option<Duration>,
+}
+
+impl Default for SimParams {
+    fn default() -> Self {
+        Self {
+            growth_scale: 1.0,
+            max_living_trees: None,
+            random_tree_flip: true,
+            min_tree_spacing: 0.05,
+            seed_timer_jitter: 10.0,
+            edge_growth: None,
+            edge_policy: EdgePolicy::Drop,
+            day_length: None,
+            season_length: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub const ALL: [Season; 4] = [Season::Spring, Season::Summer, Season::Autumn, Season::Winter];
+
+    /// Scales living trees' growth, decaying Snags and Stumps ignore it.
+    pub fn growth_multiplier(&self) -> f32 {
+        match self {
+            Self::Spring => 1.0,
+            Self::Summer => 1.2,
+            Self::Autumn => 0.8,
+            Self::Winter => 0.2,
+        }
+    }
+
+    pub fn allows_seeding(&self) -> bool {
+        *self != Self::Winter
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct DebugFlags {
     pub show_grid: bool,
     pub show_dual: bool,
     pub show_trees: bool,
+    pub hide_apron: bool,
+    pub show_debug_vectors: bool,
+    pub slow_motion_on_die_off: bool,
+    pub show_slot_indices: bool,
+    pub show_seed_dispersal: bool,
+    pub show_origin: bool,
 }
 
+/// A seed that took root, from the tree that dropped it to where it landed. Only recorded while show_seed_dispersal is on.
+#[derive(Clone, Copy, Debug)]
+pub struct SeedDispersal {
+    pub from: WorldPosition,
+    pub to: WorldPosition,
+    pub age_s: f32,
+}
+
+impl SeedDispersal {
+    // Simulated seconds a dispersal stays visible for, fading out as it goes.
+    pub const FADE_S: f32 = 1.0;
+}
+
+// NOTE: Cloning lets tools branch the simulation, the rng is cloned too so both branches see the same rolls.
+#[derive(Clone)]
 pub struct GameState {
     pub camera: GameCamera,
-    pub tiles: [(GroundCover, SoilType); GRID_SIZE],
-    pub tile_light_amt: [f32; GRID_SIZE],
+    // Tiles per side, every per tile array below is grid_dim * grid_dim long.
+    grid_dim: usize,
+    pub tiles: Box<[(GroundCover, SoilType)]>,
+    pub tile_light_amt: Box<[f32]>,
+    // 0.0 => Dirt, 1.0 => Grass. Chases the tile's GroundCover so the renderer can fade between the two.
+    pub tile_grass_amt: Box<[f32]>,
+    changed_tiles: Vec<usize>,
 
     pub count_trees: usize,
-    pub per_tile_tree_count: [u8; GRID_SIZE],
-    pub trees: [Option<Tree>; MAX_NUM_TREES],
+    pub per_tile_tree_count: Box<[u8]>,
+    // NUM_TREES_PER_TILE slots per tile.
+    pub trees: Box<[Option<Tree>]>,
 
     paused: bool,
+    // Number of unpaused updates so far.
+    pub tick_count: u64,
+    slow_motion: Option<SlowMotion>,
+    pub slow_motion_trigger: SlowMotionTrigger,
     pub debug: DebugFlags,
 
     //Timers...
     debug_log_timer: TargetTimer,
     perf_timer: AverageDurationTimer<20>,
 
-    rng: ThreadRng,
+    seed: u64,
+    rng: StdRng,
+    // Multiplies how much growth every tree needs to reach its next stage, 2.0 => trees take twice as long.
+    pub growth_scale: f32,
+    // Soft limit on living trees, once reached seeds stop being planted. None => bounded only by the grid.
+    pub max_living_trees: Option<usize>,
+    // Newly planted trees are flipped at random so stands don't look stamped out.
+    pub random_tree_flip: bool,
+    // Seeds can't take root closer than this to a tree on the same tile, in tiles. 0 => only tile capacity limits planting.
+    pub min_tree_spacing: f32,
+    // Up to this many seconds are added at random to a new tree's first seed timer, so a cohort planted together doesn't seed in lockstep.
+    pub seed_timer_jitter: f32,
+    // Scales simulated time per update, 0 freezes the sim without pausing it. Set from Input every update.
+    pub sim_speed: f32,
+    // Positional growth rule, None => position doesn't matter.
+    pub edge_growth: Option<EdgeGrowth>,
+    pub edge_policy: EdgePolicy,
+    // Length of a full day/night cycle in sim time. None => always noon.
+    pub day_length: Option<Duration>,
+    // Sim time each season lasts, a year is four of them. None => no seasons, growth and seeding stay constant.
+    pub season_length: Option<Duration>,
+    species_config: SpeciesConfig,
+    seed_dispersals: Vec<SeedDispersal>,
+
+    listeners: EventListeners,
     speed: f32,
     zoom_factor: f32,
+    // Sim time run so far, scaled by slow motion and sim speed, stops while paused.
+    elapsed: Duration,
     pub one_sec_sin: f32,
 }
 
 impl GameState {
+    /// Picks a random seed, logged so a run can be reproduced with new_seeded.
     pub fn new() -> Self {
-        let rng = rand::thread_rng();
+        Self::with_dimensions(GRID_DIM)
+    }
 
-        let camera = {
-            let x = (GRID_DIM as f32 * TILE_DIM) * 0.5;
-            let y = (GRID_DIM as f32 * TILE_DIM) * 0.5;
+    /// A grid_dim x grid_dim world with a random seed, logged like new.
+    pub fn with_dimensions(grid_dim: usize) -> Self {
+        Self::new_seeded_with_dimensions(random_logged_seed(), grid_dim)
+    }
 
-            GameCamera {
-                position: cgmath::Point3::new(x, y, -0.5),
-                zoom_level: 20.0,
-            }
-        };
+    /// Like new, with every species' growth and shade curves taken from species_config.
+    pub fn new_with_species_config(species_config: SpeciesConfig) -> Self {
+        Self::new_seeded_with_config(random_logged_seed(), GRID_DIM, species_config)
+    }
+
+    /// Same seed and same sequence of Inputs => same simulation.
+    pub fn new_seeded(seed: u64) -> Self {
+        Self::new_seeded_with_dimensions(seed, GRID_DIM)
+    }
+
+    pub fn new_seeded_with_dimensions(seed: u64, grid_dim: usize) -> Self {
+        Self::new_seeded_with_config(seed, grid_dim, SpeciesConfig::default())
+    }
+
+    /// Exactly the setup the scenario describes, the same scenario always builds the same state.
+    pub fn from_scenario(scenario: &Scenario) -> Result<Self, ScenarioError> {
+        if checked_grid_size(scenario.grid_dim).is_none() {
+            return Err(ScenarioError::WrongGridSize);
+        }
+
+        let mut result = Self::with_params(scenario.seed, scenario.grid_dim, scenario.species_config.clone(), &scenario.params);
+
+        match &scenario.terrain {
+            ScenarioTerrain::Generated => result.generate_stony_soil(),
+            ScenarioTerrain::Uniform(soil) => {
+                for tile in result.tiles.iter_mut() {
+                    tile.1 = *soil;
+                }
+            },
+            ScenarioTerrain::Tiles(tiles) => {
+                if tiles.len() != result.grid_size() {
+                    return Err(ScenarioError::WrongGridSize);
+                }
+
+                result.tiles = tiles.clone().into_boxed_slice();
+                result.tile_grass_amt = tiles.iter().map(|tile| if tile.0 == GroundCover::Grass { 1.0 } else { 0.0 }).collect();
+            },
+        }
+
+        if scenario.random_trees {
+            result.plant_random_trees();
+        }
+
+        for (index, planting) in scenario.plantings.iter().enumerate() {
+            result.plant_grown_tree(planting.position, planting.species, planting.stage)
+                .map_err(|error| ScenarioError::Planting { index, error })?;
+        }
+
+        Ok(result)
+    }
+
+    fn new_seeded_with_config(seed: u64, grid_dim: usize, species_config: SpeciesConfig) -> Self {
+        let mut result = Self::with_params(seed, grid_dim, species_config, &SimParams::default());
+        result.generate_stony_soil();
+        result.plant_random_trees();
+        result
+    }
 
-        let mut result = Self {
-            tiles: [(GroundCover::Grass, SoilType::Normal); GRID_SIZE],
-            tile_light_amt: [0.0; GRID_SIZE],
+    /// Bare grid, all grass on normal soil with no trees.
+    fn with_params(seed: u64, grid_dim: usize, species_config: SpeciesConfig, params: &SimParams) -> Self {
+        debug_assert!(grid_dim > 0);
+
+        let grid_size = grid_dim * grid_dim;
+        let rng = StdRng::seed_from_u64(seed);
+
+        let camera = GameCamera::framed(CameraFraming::default(), grid_dim);
+
+        Self {
+            grid_dim,
+            tiles: vec![(GroundCover::Grass, SoilType::Normal); grid_size].into_boxed_slice(),
+            tile_light_amt: vec![1.0; grid_size].into_boxed_slice(),
+            tile_grass_amt: vec![1.0; grid_size].into_boxed_slice(),
+            changed_tiles: Vec::new(),
             count_trees: 0,
-            per_tile_tree_count: [0; GRID_SIZE],
-            trees: [None; MAX_NUM_TREES],
+            per_tile_tree_count: vec![0; grid_size].into_boxed_slice(),
+            trees: vec![None; grid_size * NUM_TREES_PER_TILE].into_boxed_slice(),
 
             paused: false,
+            tick_count: 0,
+            slow_motion: None,
+            slow_motion_trigger: SlowMotionTrigger::default(),
             debug: DebugFlags {
                 show_dual: false,
                 show_grid: false,
                 show_trees: true,
+                hide_apron: false,
+                show_debug_vectors: false,
+                slow_motion_on_die_off: false,
+                show_slot_indices: false,
+                show_seed_dispersal: false,
+                show_origin: false,
             },
 
             debug_log_timer: TargetTimer::new(Duration::from_secs(1)),
             perf_timer: AverageDurationTimer::new(),
 
+            seed,
             rng,
+            growth_scale: params.growth_scale,
+            max_living_trees: params.max_living_trees,
+            random_tree_flip: params.random_tree_flip,
+            min_tree_spacing: params.min_tree_spacing,
+            seed_timer_jitter: params.seed_timer_jitter,
+            sim_speed: 1.0,
+            edge_growth: params.edge_growth,
+            edge_policy: params.edge_policy,
+            day_length: params.day_length,
+            season_length: params.season_length,
+            species_config,
+            seed_dispersals: Vec::new(),
+            listeners: EventListeners::default(),
             speed: 0.005,
             zoom_factor: 0.01, // percent of current zoom level
+            elapsed: Duration::ZERO,
             one_sec_sin: 0.0,
             camera,
-        };
+        }
+    }
+
+    /// Random walk of stony soil, covering up to 3/4 of the grid.
+    fn generate_stony_soil(&mut self) {
+        let grid_dim = self.grid_dim;
+        let grid_size = self.grid_size();
 
         let mut stoney_places = Vec::new();
-        stoney_places.push((result.rng.gen_range(0..GRID_DIM), result.rng.gen_range(0..GRID_DIM)));
+        stoney_places.push((self.rng.gen_range(0..grid_dim), self.rng.gen_range(0..grid_dim)));
 
-        for _ in 0..(((GRID_DIM * GRID_DIM) as f32 * 0.75) as usize) {
+        for _ in 0..((grid_size as f32 * 0.75) as usize) {
             let dirs = [
                 (0,  1),
                 (0, -1),
@@ -144,53 +529,58 @@ impl GameState {
                 (-1, 0)
             ];
 
-            let dir = dirs[result.rng.gen_range(0..dirs.len())];
+            let dir = dirs[self.rng.gen_range(0..dirs.len())];
 
             let mut new = *(stoney_places.last().unwrap());
-            new.0 = (new.0 as i32 + dir.0).clamp(0, (GRID_DIM-1) as i32) as usize;
-            new.1 = (new.1 as i32 + dir.1).clamp(0, (GRID_DIM-1) as i32) as usize;
+            new.0 = (new.0 as i32 + dir.0).clamp(0, (grid_dim-1) as i32) as usize;
+            new.1 = (new.1 as i32 + dir.1).clamp(0, (grid_dim-1) as i32) as usize;
 
             stoney_places.push(new);
         }
 
 
         for (x, y) in stoney_places {
-            let index = tile_index!(x, y);
-            result.tiles.get_mut(index).unwrap().1 = SoilType::Stony;
+            let index = tile_index!(x, y, grid_dim);
+            self.tiles.get_mut(index).unwrap().1 = SoilType::Stony;
         }
+    }
+
+    /// Scatters random species over random positions, the count scaled by area.
+    fn plant_random_trees(&mut self) {
+        let grid_dim = self.grid_dim;
+        let grid_size = self.grid_size();
 
+        // On the default grid, scaled by area for others.
         const NUM_INITIAL_TREES: usize = 110;
-        let mut plant_locations = Vec::with_capacity(NUM_INITIAL_TREES * 2);
-        for _ in 0..NUM_INITIAL_TREES {
+        let num_initial_trees = NUM_INITIAL_TREES * grid_size / GRID_SIZE;
+        let mut plant_locations = Vec::with_capacity(num_initial_trees * 2);
+        for _ in 0..num_initial_trees {
             plant_locations.push(
                 WorldPosition {
                     coord: TileCoordinate {
-                        x: result.rng.gen_range(0..GRID_DIM) as i32,
-                        y: result.rng.gen_range(0..GRID_DIM) as i32,
+                        x: self.rng.gen_range(0..grid_dim) as i32,
+                        y: self.rng.gen_range(0..grid_dim) as i32,
                     },
                     offset: TileOffset {
-                        x: result.rng.gen_range(0.0..1.0),
-                        y: result.rng.gen_range(0.0..1.0),
+                        x: self.rng.gen_range(0.0..1.0),
+                        y: self.rng.gen_range(0.0..1.0),
                     },
                 }
             );
         }
 
-        let species = [
-            TreeSpecies::Ash,
-            TreeSpecies::Fir,
-            TreeSpecies::CottonWood,
-        ];
+        let species = TreeSpecies::ALL;
 
         for pos in plant_locations {
-            let index = result.rng.gen_range(0..species.len());
-            result.plant_tree(pos, species[index]);
-            // result.plant_tree(pos, TreeSpecies::Ash);
+            let index = self.rng.gen_range(0..species.len());
+            // Picks landing on top of an earlier one are just left empty.
+            let _ = self.plant_tree(pos, species[index]);
+            // self.plant_tree(pos, TreeSpecies::Ash);
         }
-
-        result
     }
 
+    /// # Safety
+    /// tile_index must be < grid_size.
     pub unsafe fn iter_trees_on_tile_unchecked_mut<'s, 't>(&'s mut self, tile_index: usize) -> impl Iterator<Item=&'t mut Tree>
     where
         's: 't
@@ -202,11 +592,11 @@ impl GameState {
         let begin = tree_slot_index!(tile_index, 0);
         let end = begin + num_trees_on_tile;
 
-        debug_assert!(end <= MAX_NUM_TREES);
+        debug_assert!(end <= self.trees.len());
 
         // SAFETY:
         //  begin is usize, cannot be < 0
-        //  end is <= MAX_NUM_TREES
+        //  end is <= self.trees.len()
         let slice = self.trees.get_unchecked_mut(begin..end);
 
         // SAEFTY:
@@ -216,6 +606,8 @@ impl GameState {
         result
     }
 
+    /// # Safety
+    /// tile_index must be < grid_size.
     pub unsafe fn iter_trees_on_tile_unchecked<'s, 't>(&'s self, tile_index: usize) -> impl Iterator<Item=&'t Tree>
     where
         's: 't
@@ -227,7 +619,7 @@ impl GameState {
         let begin = tree_slot_index!(tile_index, 0);
         let end = begin + num_trees_on_tile;
 
-        debug_assert!(end <= MAX_NUM_TREES);
+        debug_assert!(end <= self.trees.len());
 
         // SAFETY:
         //  tile_index assumed to be in bounds.
@@ -242,10 +634,8 @@ impl GameState {
     }
 
 
-    pub fn iter_trees_in_radius<'s, 't>(&'s self, pos: WorldPosition, radius: f32) -> impl Iterator<Item=(usize, &'t Tree)>
-    where
-        's: 't
-    {
+    /// Every tree on the tiles overlapping the square around pos, corners included.
+    fn iter_trees_in_bounding_square(&self, pos: WorldPosition, radius: f32) -> TreeRegionIterator<'_> {
         let radius_offset = TileOffset { x: radius, y: radius };
 
         let min = pos - radius_offset;
@@ -253,15 +643,37 @@ impl GameState {
         let min_y = i32::max(min.coord.y, 0) as usize;
 
         let max = pos + radius_offset;
-        let max_x = i32::min(max.coord.x, GRID_DIM as i32 - 1) as usize;
-        let max_y = i32::min(max.coord.y, GRID_DIM as i32 - 1) as usize;
+        let max_x = i32::min(max.coord.x, self.grid_dim as i32 - 1) as usize;
+        let max_y = i32::min(max.coord.y, self.grid_dim as i32 - 1) as usize;
 
         // SAEFTY:
         //  Trees min, max have jsut been clamped against bounds
-        unsafe {
-            TreeRegionIterator::new((min_x, min_y), (max_x, max_y), &self.trees)
-                .filter(move |t| t.1.position.distance_sq(&pos) <= (radius * radius))
-        }
+        unsafe { TreeRegionIterator::new((min_x, min_y), (max_x, max_y), self.grid_dim, &self.trees) }
+    }
+
+    pub fn iter_trees_in_radius<'s, 't>(&'s self, pos: WorldPosition, radius: f32) -> impl Iterator<Item=(usize, &'t Tree)>
+    where
+        's: 't
+    {
+        self.iter_trees_in_bounding_square(pos, radius)
+            .filter(move |t| t.1.position.distance_sq(&pos) <= (radius * radius))
+    }
+
+    /// Closest tree to pos within max_radius, dead or alive. Ties go to the lowest slot index.
+    pub fn nearest_tree(&self, pos: WorldPosition, max_radius: f32) -> Option<(usize, &Tree)> {
+        self.iter_trees_in_bounding_square(pos, max_radius)
+            .map(|(slot_index, tree)| (slot_index, tree, tree.position.distance_sq(&pos)))
+            .filter(|&(_, _, dist_sq)| dist_sq <= (max_radius * max_radius))
+            .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap())
+            .map(|(slot_index, tree, _)| (slot_index, tree))
+    }
+
+    /// iter_trees_in_radius without the Snags and Stumps.
+    pub fn iter_living_trees_in_radius<'s, 't>(&'s self, pos: WorldPosition, radius: f32) -> impl Iterator<Item=(usize, &'t Tree)>
+    where
+        's: 't
+    {
+        self.iter_trees_in_radius(pos, radius).filter(|t| t.1.is_alive())
     }
 
     pub fn iter_trees_in_radius_mut<'s, 't>(&'s mut self, pos: WorldPosition, radius: f32) -> impl Iterator<Item=(usize, &'t mut Tree)>
@@ -275,17 +687,333 @@ impl GameState {
         let min_y = i32::max(min.coord.y, 0) as usize;
 
         let max = pos + radius_offset;
-        let max_x = i32::min(max.coord.x, GRID_DIM as i32 - 1) as usize;
-        let max_y = i32::min(max.coord.y, GRID_DIM as i32 - 1) as usize;
+        let max_x = i32::min(max.coord.x, self.grid_dim as i32 - 1) as usize;
+        let max_y = i32::min(max.coord.y, self.grid_dim as i32 - 1) as usize;
 
         // SAEFTY:
         //  Trees min, max have jsut been clamped against bounds
         unsafe {
-            TreeRegionIteratorMut::new((min_x, min_y), (max_x, max_y), &mut self.trees)
+            TreeRegionIteratorMut::new((min_x, min_y), (max_x, max_y), self.grid_dim, &mut self.trees)
                 .filter(move |t| t.1.position.distance_sq(&pos) <= (radius * radius))
         }
     }
 
+    /// Tiles per side of the grid.
+    pub fn grid_dim(&self) -> usize {
+        self.grid_dim
+    }
+
+    /// Furthest the camera zooms out, far enough to fit the whole grid twice over so huge maps can be taken in at once.
+    pub fn max_zoom(&self) -> f32 {
+        f32::max(MAX_ZOOM, 2.0 * self.grid_dim as f32 * TILE_DIM)
+    }
+
+    pub fn grid_size(&self) -> usize {
+        self.grid_dim * self.grid_dim
+    }
+
+    /// Hash of the tick count, ground and every tree. Runs from the same seed and inputs should agree tick for tick,
+    /// the first mismatch pins down where determinism broke.
+    pub fn checksum(&self) -> u64 {
+        use std::hash::Hasher;
+
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        hasher.write_u64(self.tick_count);
+        hasher.write_usize(self.grid_dim);
+
+        for (&(cover, soil), grass_amt) in self.tiles.iter().zip(self.tile_grass_amt.iter()) {
+            hasher.write_u8(cover as u8);
+            hasher.write_u8(soil as u8);
+            hasher.write_u32(grass_amt.to_bits());
+        }
+
+        for (slot_index, tree) in self.iter_all_trees() {
+            hasher.write_usize(slot_index);
+            hasher.write_u8(tree.species as u8);
+            hasher.write_u8(tree.stage as u8);
+            hasher.write_i32(tree.position.coord.x);
+            hasher.write_i32(tree.position.coord.y);
+            hasher.write_u32(tree.position.offset.x.to_bits());
+            hasher.write_u32(tree.position.offset.y.to_bits());
+            hasher.write_u32(tree.growth.to_bits());
+            hasher.write_u32(tree.shade_factor.to_bits());
+            hasher.write_u32(tree.seed_timer.to_bits());
+            hasher.write_u8(tree.burning as u8);
+        }
+
+        hasher.finish()
+    }
+
+    /// Seed this state was created with, or last reseeded with, see new_seeded.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn species_config(&self) -> &SpeciesConfig {
+        &self.species_config
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// Sims start in spring. None if seasons are disabled.
+    pub fn current_season(&self) -> Option<Season> {
+        let season_length = self.season_length.filter(|length| !length.is_zero())?;
+        let seasons_elapsed = (self.elapsed.as_secs_f64() / season_length.as_secs_f64()) as usize;
+
+        Some(Season::ALL[seasons_elapsed % Season::ALL.len()])
+    }
+
+    /// Brightness of the scene, 1.0 at noon falling to MIN_AMBIENT_LIGHT at midnight. Sims start at noon.
+    pub fn ambient_light(&self) -> f32 {
+        let day_length = match self.day_length {
+            Some(day_length) if !day_length.is_zero() => day_length,
+            _ => return 1.0,
+        };
+
+        let phase = (self.elapsed.as_secs_f64() / day_length.as_secs_f64()).fract() as f32;
+        let daylight = f32::cos(phase * std::f32::consts::TAU) * 0.5 + 0.5;
+
+        MIN_AMBIENT_LIGHT + (1.0 - MIN_AMBIENT_LIGHT) * daylight
+    }
+
+    pub fn seed_dispersals(&self) -> &[SeedDispersal] {
+        &self.seed_dispersals
+    }
+
+    /// Swaps in a fresh random stream, the world itself is untouched.
+    pub fn reseed_rng(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+
+    /// Every tree in the world with its slot index, tile by tile.
+    pub fn iter_all_trees(&self) -> impl Iterator<Item=(usize, &Tree)> {
+        self.trees.chunks(NUM_TREES_PER_TILE)
+            .zip(self.per_tile_tree_count.iter())
+            .enumerate()
+            .flat_map(|(tile_index, (slots, &count))| {
+                // Trees are packed in the front of each tile's slots.
+                slots[..count as usize].iter()
+                    .enumerate()
+                    .filter_map(move |(tree_index, t)| t.as_ref().map(|t| (tree_slot_index!(tile_index, tree_index), t)))
+            })
+    }
+
+    pub fn iter_all_trees_mut(&mut self) -> impl Iterator<Item=(usize, &mut Tree)> {
+        self.trees.chunks_mut(NUM_TREES_PER_TILE)
+            .zip(self.per_tile_tree_count.iter())
+            .enumerate()
+            .flat_map(|(tile_index, (slots, &count))| {
+                slots[..count as usize].iter_mut()
+                    .enumerate()
+                    .filter_map(move |(tree_index, t)| t.as_mut().map(|t| (tree_slot_index!(tile_index, tree_index), t)))
+            })
+    }
+
+    /// (min, max) corners of the box around every living tree, None if nothing is alive.
+    pub fn forest_bounds(&self) -> Option<(WorldPosition, WorldPosition)> {
+        // Offsets are normalized to [0, 1), so ordering by (coord, offset) per axis is exact.
+        let before = |a: (i32, f32), b: (i32, f32)| a.0 < b.0 || (a.0 == b.0 && a.1 < b.1);
+
+        let mut living = self.iter_all_trees().map(|(_, t)| t).filter(|t| t.is_alive());
+        let first = living.next()?;
+        let (mut min, mut max) = (first.position, first.position);
+
+        for tree in living {
+            let pos = tree.position;
+            if before((pos.coord.x, pos.offset.x), (min.coord.x, min.offset.x)) { min.coord.x = pos.coord.x; min.offset.x = pos.offset.x; }
+            if before((pos.coord.y, pos.offset.y), (min.coord.y, min.offset.y)) { min.coord.y = pos.coord.y; min.offset.y = pos.offset.y; }
+            if before((max.coord.x, max.offset.x), (pos.coord.x, pos.offset.x)) { max.coord.x = pos.coord.x; max.offset.x = pos.offset.x; }
+            if before((max.coord.y, max.offset.y), (pos.coord.y, pos.offset.y)) { max.coord.y = pos.coord.y; max.offset.y = pos.offset.y; }
+        }
+
+        Some((min, max))
+    }
+
+    /// The Snags and Stumps, dead wood that still stands in the world.
+    pub fn iter_deadwood(&self) -> impl Iterator<Item=(usize, &Tree)> {
+        self.iter_all_trees().filter(|(_, t)| !t.is_alive())
+    }
+
+    /// Unlike count_trees, this skips Snags and Stumps which still occupy a slot.
+    pub fn count_living_trees(&self) -> usize {
+        self.iter_all_trees().filter(|(_, t)| t.is_alive()).count()
+    }
+
+    /// Age distribution of living trees, Snags and Stumps are skipped like in count_living_trees.
+    pub fn age_stats(&self) -> AgeStats {
+        let mut ages: Vec<f32> = self.iter_all_trees()
+            .filter(|(_, t)| t.is_alive())
+            .map(|(_, t)| t.age_s)
+            .collect();
+
+        let mut result = AgeStats::default();
+        if ages.is_empty() {
+            return result;
+        }
+
+        ages.sort_unstable_by(|a, b| a.partial_cmp(b).unwrap());
+
+        let count = ages.len();
+        result.count = count;
+        result.mean_s = ages.iter().sum::<f32>() / count as f32;
+        result.median_s = if count & 1 == 1 {
+            ages[count / 2]
+        } else {
+            (ages[count / 2 - 1] + ages[count / 2]) * 0.5
+        };
+        result.max_s = ages[count - 1];
+
+        for age in ages {
+            let bin = ((age / AgeStats::HISTOGRAM_BIN_WIDTH_S) as usize).min(AgeStats::HISTOGRAM_BINS - 1);
+            result.histogram[bin] += 1;
+        }
+
+        result
+    }
+
+    /// Indices of tiles whose GroundCover changed during the last update.
+    pub fn changed_tiles(&self) -> &[usize] {
+        &self.changed_tiles
+    }
+
+    /// Rough fraction of the grid covered by canopy (0.0 bare -> 1.0 fully shaded), derived from last tick's tile light.
+    pub fn canopy_coverage(&self) -> f32 {
+        let shade_sum: f32 = self.tile_light_amt.iter().map(|light| 1.0 - light).sum();
+        shade_sum / self.grid_size() as f32
+    }
+
+    /// Last tick's tile light as a grid_dim x grid_dim image, black => fully shaded, white => full light.
+    /// Rows are flipped so the image reads the same way the grid is drawn (y up).
+    pub fn export_light_map(&self) -> image::GrayImage {
+        image::GrayImage::from_fn(self.grid_dim as u32, self.grid_dim as u32, |x, y| {
+            let light = self.tile_light_amt[tile_index!(x, self.grid_dim as u32 - 1 - y, self.grid_dim)];
+            image::Luma([(light.clamp(0.0, 1.0) * 255.0).round() as u8])
+        })
+    }
+
+    pub fn frame_camera(&mut self, framing: CameraFraming) {
+        self.camera = GameCamera::framed(framing, self.grid_dim);
+    }
+
+    /// Centers on the living trees, zoomed out just far enough to see them all plus a tile of margin.
+    /// Leaves the camera alone when nothing is alive.
+    pub fn fit_to_forest(&mut self, aspect_ratio: f32) {
+        let (min, max) = match self.forest_bounds() {
+            Some(bounds) => bounds,
+            None => return,
+        };
+
+        let to_world = |coord: i32, offset: f32| (coord as f32 + offset) * TILE_DIM;
+        let (min_x, min_y) = (to_world(min.coord.x, min.offset.x), to_world(min.coord.y, min.offset.y));
+        let (max_x, max_y) = (to_world(max.coord.x, max.offset.x), to_world(max.coord.y, max.offset.y));
+
+        let width  = (max_x - min_x) + (2.0 * TILE_DIM);
+        let height = (max_y - min_y) + (2.0 * TILE_DIM);
+
+        self.frame_camera(CameraFraming::Explicit {
+            position: cgmath::Point2::new((min_x + max_x) * 0.5, (min_y + max_y) * 0.5),
+            zoom_level: f32::max(height, width / aspect_ratio),
+        });
+    }
+
+    /// Tile under a world space point, None for anything outside the drawn grid (0..grid_dim * TILE_DIM on both axes).
+    pub fn world_to_tile(&self, world: cgmath::Point2<f32>) -> Option<TileCoordinate> {
+        let max = self.grid_dim as f32 * TILE_DIM;
+
+        if !world.x.is_finite() || !world.y.is_finite() { return None; }
+        if world.x < 0.0 || world.x >= max { return None; }
+        if world.y < 0.0 || world.y >= max { return None; }
+
+        // Clamped since rounding can push a point just under max onto grid_dim.
+        Some(TileCoordinate {
+            x: ((world.x / TILE_DIM).floor() as i32).min(self.grid_dim as i32 - 1),
+            y: ((world.y / TILE_DIM).floor() as i32).min(self.grid_dim as i32 - 1),
+        })
+    }
+
+    /// Returns false if (x, y) is off the grid. Trees on the tile feel the new soil from the next update, the ground
+    /// is rebuilt from `tiles` every frame so the stone layer follows along.
+    pub fn set_soil(&mut self, x: i32, y: i32, soil: SoilType) -> bool {
+        if x < 0 || x >= self.grid_dim as i32 || y < 0 || y >= self.grid_dim as i32 { return false; }
+
+        self.tiles[tile_index!(x, y, self.grid_dim)].1 = soil;
+        true
+    }
+
+    /// Species that would survive being planted on (x, y) given its soil and last tick's light, using the same
+    /// growth multiplier cutoff that kills living trees. Empty if (x, y) is off the grid.
+    pub fn suitable_species(&self, x: i32, y: i32) -> Vec<TreeSpecies> {
+        if x < 0 || x >= self.grid_dim as i32 || y < 0 || y >= self.grid_dim as i32 { return Vec::new(); }
+
+        let tile_index = tile_index!(x, y, self.grid_dim);
+        let soil_type = self.tiles[tile_index].1;
+        let light = self.tile_light_amt[tile_index];
+
+        TreeSpecies::ALL.iter()
+            .copied()
+            .filter(|species| {
+                let soil_multiplier = if soil_type == species.soil_preference() { 1.0 } else { SOIL_MISMATCH_GROWTH_MULTIPLIER };
+                light * soil_multiplier > MIN_GROWTH_MULTIPLIER
+            })
+            .collect()
+    }
+
+    /// Tile the slot belongs to, whether or not it holds a tree. None if the slot is out of range.
+    pub fn tile_of_tree(&self, tree_slot_index: usize) -> Option<TileCoordinate> {
+        if tree_slot_index >= self.trees.len() { return None; }
+
+        let tile_index = tile_index_of_slot!(tree_slot_index);
+        Some(TileCoordinate {
+            x: (tile_index % self.grid_dim) as i32,
+            y: (tile_index / self.grid_dim) as i32,
+        })
+    }
+
+    /// Indices of the in-bounds tiles around tile_index, never tile_index itself. Up to 8 with diagonals, 4 without.
+    pub fn neighbors(&self, tile_index: usize, diagonals: bool) -> impl Iterator<Item=usize> {
+        let grid_dim = self.grid_dim as i32;
+        let x = (tile_index % self.grid_dim) as i32;
+        let y = (tile_index / self.grid_dim) as i32;
+
+        (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+            .filter(move |&(dx, dy)| (dx, dy) != (0, 0) && (diagonals || dx == 0 || dy == 0))
+            .map(move |(dx, dy)| (x + dx, y + dy))
+            .filter(move |&(nx, ny)| (nx >= 0) && (nx < grid_dim) && (ny >= 0) && (ny < grid_dim))
+            .map(move |(nx, ny)| tile_index!(nx, ny, grid_dim as usize))
+    }
+
+    /// Grass tiles among the up to 8 around tile_index, the tile itself doesn't count. Drives dirt regrowing grass.
+    pub fn grassy_neighbor_count(&self, tile_index: usize) -> usize {
+        self.neighbors(tile_index, true)
+            .filter(|&neighbor_index| self.tiles[neighbor_index].0 == GroundCover::Grass)
+            .count()
+    }
+
+    /// Slot of the tree on pos's tile closest to pos, provided it is within tolerance.
+    pub fn tree_slot_for_position(&self, pos: WorldPosition, tolerance: f32) -> Option<usize> {
+        let x = pos.coord.x;
+        let y = pos.coord.y;
+
+        if x < 0 || x >= self.grid_dim as i32 || y < 0 || y >= self.grid_dim as i32 {
+            return None;
+        }
+
+        let tile_index = tile_index!(x, y, self.grid_dim);
+
+        // SAFETY:
+        //  We've just checked that x, y are in bounds
+        unsafe { self.iter_trees_on_tile_unchecked(tile_index) }
+            .enumerate()
+            .map(|(tree_index, tree)| (tree_slot_index!(tile_index, tree_index), tree.position.distance_sq(&pos)))
+            .filter(|&(_, dist_sq)| dist_sq <= (tolerance * tolerance))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(slot_index, _)| slot_index)
+    }
+
     unsafe fn get_tree_slots_on_tile_unchecked_mut(&mut self, tile_index: usize) -> & mut[Option<Tree>] {
         let begin = tree_slot_index!(tile_index, 0);
         let end = tree_slot_index!(tile_index, NUM_TREES_PER_TILE);
@@ -295,16 +1023,65 @@ impl GameState {
         self.trees.get_unchecked_mut(begin..end)
     }
 
-    fn plant_tree(&mut self, pos: WorldPosition, species: TreeSpecies)  {
+    /// Plants a tree at pos, returning the new tree's slot index. Fails if pos is off the grid or its tile is full.
+    pub fn try_plant_tree(&mut self, pos: WorldPosition, species: TreeSpecies) -> Result<usize, PlantError> {
+        if pos.coord.x < 0 || pos.coord.x >= self.grid_dim as i32 || pos.coord.y < 0 || pos.coord.y >= self.grid_dim as i32 {
+            return Err(PlantError::OutOfBounds);
+        }
+
+        let tree_slot_index = self.plant_tree(pos, species)?;
+
+        if !self.listeners.is_empty() {
+            self.listeners.notify(&SimEvent::Planted { position: pos, species });
+        }
+
+        Ok(tree_slot_index)
+    }
+
+    /// Like try_plant_tree, with the new tree already grown into stage and shading its neighbors accordingly.
+    fn plant_grown_tree(&mut self, pos: WorldPosition, species: TreeSpecies, stage: TreeGrowthStage) -> Result<usize, PlantError> {
+        if pos.coord.x < 0 || pos.coord.x >= self.grid_dim as i32 || pos.coord.y < 0 || pos.coord.y >= self.grid_dim as i32 {
+            return Err(PlantError::OutOfBounds);
+        }
+
+        let tree_slot_index = self.plant_tree(pos, species)?;
+
+        if stage != TreeGrowthStage::Sprout {
+            let tree = self.trees[tree_slot_index].as_mut().unwrap();
+            let grown = Tree::builder(species, pos)
+                .stage(stage)
+                .shade_factor(tree.shade_factor)
+                .build(&self.species_config);
+
+            // Keep the rolls plant_tree made (flip, seed timer jitter).
+            tree.stage = grown.stage;
+            tree.growth = grown.growth;
+            tree.growth_target = grown.growth_target;
+
+            self.update_shade_for_surrounding_trees(tree_slot_index, TreeGrowthStage::Sprout);
+        }
+
+        Ok(tree_slot_index)
+    }
+
+    /// Fails if the tile is already full or pos is too close to one of its trees.
+    fn plant_tree(&mut self, pos: WorldPosition, species: TreeSpecies) -> Result<usize, PlantError> {
         let x = pos.coord.x;
         let y = pos.coord.y;
 
-        debug_assert!(x < GRID_DIM as i32);
+        debug_assert!(x < self.grid_dim as i32);
         debug_assert!(x >= 0);
-        debug_assert!(y < GRID_DIM as i32);
+        debug_assert!(y < self.grid_dim as i32);
         debug_assert!(y >= 0);
 
-        let tile_index = tile_index!(x, y);
+        let tile_index = tile_index!(x, y, self.grid_dim);
+
+        // Walks the raw slots rather than iter_trees_on_tile, update_trees plants while deleted trees still leave holes.
+        let min_spacing_sq = self.min_tree_spacing * self.min_tree_spacing;
+        let tile_slots = &self.trees[tree_slot_index!(tile_index, 0)..tree_slot_index!(tile_index, NUM_TREES_PER_TILE)];
+        if self.min_tree_spacing > 0.0 && tile_slots.iter().flatten().any(|tree| tree.position.distance_sq(&pos) <= min_spacing_sq) {
+            return Err(PlantError::TooClose);
+        }
 
         // SAEFTY:
         //  We've just checked that x, y are in bounds
@@ -318,7 +1095,13 @@ impl GameState {
 
             debug_assert!(tree_opt.is_none());
 
-            *(tree_opt) = Some(Tree::new(species, pos));
+            let mut tree = Tree::new(species, pos, &self.species_config);
+            tree.flipped = self.random_tree_flip && self.rng.gen();
+            if self.seed_timer_jitter > 0.0 {
+                tree.seed_timer += self.rng.gen_range(0.0..self.seed_timer_jitter);
+            }
+
+            *(tree_opt) = Some(tree);
 
             // SAEFTY:
             //  We've just checked the x, y uset to create tile_index
@@ -326,11 +1109,15 @@ impl GameState {
             self.count_trees += 1;
 
             self.set_shade_from_surrounding_trees(tree_slot_index);
+
+            Ok(tree_slot_index)
+        } else {
+            Err(PlantError::TileFull)
         }
     }
 
     fn kill_tree(&mut self, tree_slot_index: usize)  {
-        debug_assert!(tree_slot_index < MAX_NUM_TREES);
+        debug_assert!(tree_slot_index < self.trees.len());
         let tree_stage = self.trees.get(tree_slot_index).unwrap().as_ref().unwrap().stage;
 
         use TreeGrowthStage::*;
@@ -339,17 +1126,75 @@ impl GameState {
             Sapling | Mature | Old | Decline => {
                 let tree = self.trees.get_mut(tree_slot_index).unwrap().as_mut().unwrap();
                 tree.stage = Snag;
-                tree.growth_target = tree.growth_required_for_next_stage();
+                tree.burning = false;
+                tree.growth_target = tree.growth_required_for_next_stage(&self.species_config);
             },
             Snag | Stump => (), // already dead
         }
     }
 
-    /// SAFETY: A group of N calls to this function _MUST_ be followed by a call to pack_trees() otherwise tree iteration invariants are broken. lol
-    unsafe fn delete_tree(&mut self, tree_slot_index: usize)  {
-        debug_assert!(tree_slot_index < MAX_NUM_TREES);
-
-        let tile_index = tree_slot_index / NUM_TREES_PER_TILE;
+    /// Removes the tree closest to pos on pos's tile, dead or alive, and repacks the tile. Returns false if the tile
+    /// is off the grid or empty.
+    pub fn remove_tree_at(&mut self, pos: WorldPosition) -> bool {
+        let x = pos.coord.x;
+        let y = pos.coord.y;
+
+        if x < 0 || x >= self.grid_dim as i32 || y < 0 || y >= self.grid_dim as i32 {
+            return false;
+        }
+
+        let tile_index = tile_index!(x, y, self.grid_dim);
+
+        // SAFETY:
+        //  We've just checked that x, y are in bounds
+        let nearest = unsafe { self.iter_trees_on_tile_unchecked(tile_index) }
+            .enumerate()
+            .map(|(tree_index, tree)| (tree_slot_index!(tile_index, tree_index), tree.position.distance_sq(&pos)))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        let tree_slot_index = match nearest {
+            Some((tree_slot_index, _)) => tree_slot_index,
+            None => return false,
+        };
+
+        let tree = self.trees.get(tree_slot_index).unwrap().as_ref().unwrap();
+        let (position, species, stage) = (tree.position, tree.species, tree.stage);
+
+        // Lift its shadow off the neighbours while it's still there to measure from.
+        let shadow_radius = self.species_config.shadow_radius(species, stage);
+        self.replace_shadow_on_surrounding_trees(tree_slot_index, shadow_radius, 0.0);
+
+        if !self.listeners.is_empty() {
+            self.listeners.notify(&SimEvent::Removed { position, species });
+        }
+
+        // SAFETY:
+        //  tree_slot_index was found on tile_index, which is repacked straight after.
+        unsafe {
+            self.delete_tree(tree_slot_index);
+            self.pack_trees(tile_index);
+        }
+
+        true
+    }
+
+    /// Sets a living tree on fire, returns false if the slot is empty, dead or already burning.
+    pub fn ignite_tree(&mut self, tree_slot_index: usize) -> bool {
+        match self.trees.get_mut(tree_slot_index).and_then(|t| t.as_mut()) {
+            Some(tree) if tree.is_alive() && !tree.burning => {
+                tree.burning = true;
+                tree.burn_timer = BURN_DURATION_S;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// SAFETY: A group of N calls to this function _MUST_ be followed by a call to pack_trees() otherwise tree iteration invariants are broken. lol
+    unsafe fn delete_tree(&mut self, tree_slot_index: usize)  {
+        debug_assert!(tree_slot_index < self.trees.len());
+
+        let tile_index = tile_index_of_slot!(tree_slot_index);
         let tree_index = tree_slot_index % NUM_TREES_PER_TILE;
 
         let count_trees_on_tile = *(self.per_tile_tree_count.get_unchecked(tile_index)) as usize;
@@ -357,6 +1202,31 @@ impl GameState {
 
         let tree_slots = self.get_tree_slots_on_tile_unchecked_mut(tile_index);
         *(tree_slots.get_unchecked_mut(tree_index)) = None;
+
+        // per_tile_tree_count catches up in pack_trees.
+        self.count_trees -= 1;
+    }
+
+    /// Panics if count_trees, per_tile_tree_count and the packed slots disagree, everything unsafe about trees relies on them.
+    #[cfg(debug_assertions)]
+    fn assert_tree_counts_in_sync(&self) {
+        let mut sum = 0;
+        for (tile_index, &count) in self.per_tile_tree_count.iter().enumerate() {
+            let count = count as usize;
+            let begin = tree_slot_index!(tile_index, 0);
+            let slots = &self.trees[begin..(begin + NUM_TREES_PER_TILE)];
+
+            let occupied = slots.iter().filter(|t| t.is_some()).count();
+            assert!(
+                count <= NUM_TREES_PER_TILE && occupied == count && slots[..count].iter().all(|t| t.is_some()),
+                "Tile {} has per_tile_tree_count {} but {} occupied slots: {:?}",
+                tile_index, count, occupied, slots.iter().map(|t| t.is_some()).collect::<Vec<_>>()
+            );
+
+            sum += count;
+        }
+
+        assert!(sum == self.count_trees, "count_trees is {} but per_tile_tree_count sums to {}", self.count_trees, sum);
     }
 
     /// SAFETY: tile_index must be in bounds
@@ -381,6 +1251,27 @@ impl GameState {
         *(self.per_tile_tree_count.get_unchecked_mut(tile_index)) = write_index as u8;
     }
 
+    /// Rebuilds per_tile_tree_count and count_trees from the occupied slots and repacks every tile, for tools that edit
+    /// trees directly. Returns the number of trees found.
+    pub fn compact(&mut self) -> usize {
+        let mut count_trees = 0;
+
+        for tile_index in 0..self.grid_size() {
+            let begin = tree_slot_index!(tile_index, 0);
+            let occupied = self.trees[begin..(begin + NUM_TREES_PER_TILE)].iter().filter(|t| t.is_some()).count();
+
+            self.per_tile_tree_count[tile_index] = occupied as u8;
+            // SAFETY:
+            //  tile_index ranges from 0..grid_size
+            unsafe { self.pack_trees(tile_index) };
+
+            count_trees += occupied;
+        }
+
+        self.count_trees = count_trees;
+        count_trees
+    }
+
     pub fn set_shade_from_surrounding_trees(&mut self, tree_slot_index: usize) {
         let (tree_pos, tree_stage) = {
             let t_ref = self.trees.get(tree_slot_index).unwrap().as_ref().unwrap();
@@ -390,10 +1281,11 @@ impl GameState {
         let mut shade_factor = 1.0;
         let radius = 3.0; //Something big... No tree is gonna be 6 tiles wide... probably.
 
-        for (near_tree_index, near_tree) in self.iter_trees_in_radius(tree_pos, radius) {
+        // Dead trees don't cast shade.
+        for (near_tree_index, near_tree) in self.iter_living_trees_in_radius(tree_pos, radius) {
             if near_tree_index == tree_slot_index { continue; } // Skip the tree we're updating.
 
-            let near_tree_shad_rad = near_tree.species.shadow_radius(near_tree.stage);
+            let near_tree_shad_rad = self.species_config.shadow_radius(near_tree.species, near_tree.stage);
             let distance = tree_pos.distance(&near_tree.position);
 
             if near_tree_shad_rad <= 0.0     { continue; } // 0 causes undesirable flipping with smoothstep, negative radius should be impossible.
@@ -406,14 +1298,44 @@ impl GameState {
         t_ref_mut.shade_factor = shade_factor;
     }
 
+    /// Swap a tree's species in place, keeping its progress through the current stage and the shade it casts consistent.
+    pub fn change_species(&mut self, tree_slot_index: usize, new_species: TreeSpecies) {
+        let (old_species, stage) = {
+            let tree = self.trees.get_mut(tree_slot_index).unwrap().as_mut().unwrap();
+            let old_species = tree.species;
+
+            // growth_target is cumulative, re-target relative to where the current stage began.
+            let stage_begin = match (tree.growth_target, tree.growth_required_for_next_stage(&self.species_config)) {
+                (Some(target), Some(required)) => target - required,
+                _ => tree.growth,
+            };
+
+            tree.species = new_species;
+            tree.growth_target = tree.growth_required_for_next_stage(&self.species_config).map(|required| stage_begin + required);
+
+            (old_species, tree.stage)
+        };
+
+        let old_shadow_radius = self.species_config.shadow_radius(old_species, stage);
+        let new_shadow_radius = self.species_config.shadow_radius(new_species, stage);
+        if old_shadow_radius != new_shadow_radius {
+            self.replace_shadow_on_surrounding_trees(tree_slot_index, old_shadow_radius, new_shadow_radius);
+        }
+    }
+
     pub fn update_shade_for_surrounding_trees(&mut self, tree_slot_index: usize, previous_stage: TreeGrowthStage) {
         let t_ref = self.trees.get(tree_slot_index).unwrap().as_ref().unwrap();
-        let tree_pos = t_ref.position;
         let tree_species = t_ref.species;
         let tree_stage = t_ref.stage;
 
-        let old_shadow_radius = tree_species.shadow_radius(previous_stage);
-        let new_shadow_radius = tree_species.shadow_radius(tree_stage);
+        let old_shadow_radius = self.species_config.shadow_radius(tree_species, previous_stage);
+        let new_shadow_radius = self.species_config.shadow_radius(tree_species, tree_stage);
+
+        self.replace_shadow_on_surrounding_trees(tree_slot_index, old_shadow_radius, new_shadow_radius);
+    }
+
+    fn replace_shadow_on_surrounding_trees(&mut self, tree_slot_index: usize, old_shadow_radius: f32, new_shadow_radius: f32) {
+        let tree_pos = self.trees.get(tree_slot_index).unwrap().as_ref().unwrap().position;
         let max_shadow_radius = f32::max(old_shadow_radius, new_shadow_radius);
 
         for (near_tree_slot, near_tree) in self.iter_trees_in_radius_mut(tree_pos, max_shadow_radius) {
@@ -434,6 +1356,8 @@ impl GameState {
     }
 
     pub fn update(&mut self, input: &Input) {
+        self.changed_tiles.clear();
+
         let left_amt  = if input.left  { -self.speed * self.camera.zoom_level } else { 0.0 };
         let right_amt = if input.right {  self.speed * self.camera.zoom_level } else { 0.0 };
         let down_amt  = if input.down  { -self.speed * self.camera.zoom_level } else { 0.0 };
@@ -448,25 +1372,59 @@ impl GameState {
         let zoom_amt = self.zoom_factor * zoom_dir * self.camera.zoom_level;
 
         self.camera.zoom_level += zoom_amt;
-        if self.camera.zoom_level > 30.0 { self.camera.zoom_level = 30.0; }
-        if self.camera.zoom_level < 1.5 { self.camera.zoom_level = 1.5; }
+        if self.camera.zoom_level > self.max_zoom() { self.camera.zoom_level = self.max_zoom(); }
+        if self.camera.zoom_level < MIN_ZOOM { self.camera.zoom_level = MIN_ZOOM; }
 
-        let dt_s = input.dt.as_secs_f32();
+        let mut dt_s = input.dt.as_secs_f32();
 
         self.debug.show_grid = input.show_grid;
         self.debug.show_dual = input.show_dual;
         self.debug.show_trees = input.show_trees;
+        self.debug.hide_apron = input.hide_apron;
+        self.debug.show_debug_vectors = input.show_debug_vectors;
+        self.debug.slow_motion_on_die_off = input.slow_motion_on_die_off;
+        self.debug.show_slot_indices = input.show_slot_indices;
+        self.debug.show_seed_dispersal = input.show_seed_dispersal;
+        self.debug.show_origin = input.show_origin;
+        self.sim_speed = input.sim_speed.clamp(0.0, Input::MAX_SIM_SPEED);
+
+        if let Some(click) = input.left_click {
+            self.plant_at_click(click, input.selected_species);
+        }
+
+        if let Some(click) = input.right_click {
+            self.ignite_at_click(click);
+        }
+
+        if let Some(click) = input.middle_click {
+            self.remove_at_click(click);
+        }
 
         self.paused = input.pause;
         if self.paused { return; }
 
-        self.update_trees(dt_s);
+        self.tick_count += 1;
+        dt_s *= self.advance_slow_motion(input.dt);
+        // NOTE: A tree moves up at most one stage per update, at high speeds it catches up over the following updates.
+        dt_s *= self.sim_speed;
+
+        self.age_seed_dispersals(dt_s);
+        let count_killed = self.update_trees(dt_s);
+
+        #[cfg(debug_assertions)]
+        self.assert_tree_counts_in_sync();
+        if self.debug.slow_motion_on_die_off && count_killed >= self.slow_motion_trigger.min_kills {
+            self.slow_for(self.slow_motion_trigger.duration, self.slow_motion_trigger.scale);
+        }
 
         measure!(self.perf_timer, {
-            self.update_grass();
+            self.update_grass(dt_s);
         });
 
-        self.one_sec_sin = f32::sin(dt_s);
+        self.elapsed += Duration::from_secs_f32(dt_s);
+        // Only the fraction of a second matters, keeps the phase precise however long the sim has run.
+        let phase = self.elapsed.as_secs_f64().fract() as f32;
+        self.one_sec_sin = f32::sin(phase * std::f32::consts::TAU);
 
         // if let TimerState::Ready(_) = self.debug_log_timer.check() {
         //     self.debug_log_timer.reset();
@@ -474,38 +1432,173 @@ impl GameState {
         // }
     }
 
-    fn update_trees(&mut self, dt_s: f32) {
-        const MAX_NUM_EVENTS: usize = GRID_SIZE * NUM_TREES_PER_TILE;
+    fn age_seed_dispersals(&mut self, dt_s: f32) {
+        if !self.debug.show_seed_dispersal {
+            self.seed_dispersals.clear();
+            return;
+        }
+
+        for dispersal in self.seed_dispersals.iter_mut() {
+            dispersal.age_s += dt_s;
+        }
+        self.seed_dispersals.retain(|d| d.age_s < SeedDispersal::FADE_S);
+    }
+
+    /// Listeners are invoked as events happen during update.
+    pub fn add_event_listener(&mut self, listener: Box<dyn EventListener>) {
+        self.listeners.push(listener);
+    }
+
+    /// None for clicks off the grid.
+    fn click_to_world_position(&self, click: cgmath::Point2<f32>) -> Option<WorldPosition> {
+        let coord = self.world_to_tile(click)?;
+
+        // Same rounding as world_to_tile, keep the position on the tile it picked.
+        let offset = TileOffset {
+            x: ((click.x / TILE_DIM) - coord.x as f32).clamp(0.0, 0.999),
+            y: ((click.y / TILE_DIM) - coord.y as f32).clamp(0.0, 0.999),
+        };
+
+        Some(WorldPosition { coord, offset })
+    }
+
+    fn ignite_at_click(&mut self, click: cgmath::Point2<f32>) {
+        let pos = match self.click_to_world_position(click) {
+            Some(pos) => pos,
+            None => return,
+        };
+
+        // Trees are drawn a tile tall above their base, a click anywhere near the base counts.
+        if let Some(tree_slot_index) = self.tree_slot_for_position(pos, 0.5) {
+            self.ignite_tree(tree_slot_index);
+        }
+    }
+
+    fn plant_at_click(&mut self, click: cgmath::Point2<f32>, species: TreeSpecies) {
+        let pos = match self.click_to_world_position(click) {
+            Some(pos) => pos,
+            None => return,
+        };
+
+        if let Err(e) = self.try_plant_tree(pos, species) {
+            log::debug!("Couldn't plant {:?} at {:?}: {}", species, pos.coord, e);
+        }
+    }
+
+    fn remove_at_click(&mut self, click: cgmath::Point2<f32>) {
+        if let Some(pos) = self.click_to_world_position(click) {
+            self.remove_tree_at(pos);
+        }
+    }
+
+    /// Step the simulation `ticks` times with nothing held, keeping the current debug flags.
+    pub fn run(&mut self, ticks: usize, dt: Duration) {
+        let mut input = Input {
+            dt,
+            show_grid: self.debug.show_grid,
+            show_dual: self.debug.show_dual,
+            show_trees: self.debug.show_trees,
+            hide_apron: self.debug.hide_apron,
+            show_debug_vectors: self.debug.show_debug_vectors,
+            slow_motion_on_die_off: self.debug.slow_motion_on_die_off,
+            show_slot_indices: self.debug.show_slot_indices,
+            show_seed_dispersal: self.debug.show_seed_dispersal,
+            show_origin: self.debug.show_origin,
+            sim_speed: self.sim_speed,
+            ..Default::default()
+        };
+
+        for _ in 0..ticks {
+            input.t += dt;
+            self.update(&input);
+        }
+    }
+
+    /// Advance the simulation by dt with no user input, sim time continues from tick_count * dt.
+    pub fn step(&mut self, dt: Duration) {
+        let input = Input {
+            dt,
+            t: dt.mul_f64((self.tick_count + 1) as f64),
+            pause: false,
+            ..Default::default()
+        };
+
+        self.update(&input);
+    }
+
+    /// Number of trees in each TreeGrowthStage, indexed by the stage's discriminant.
+    pub fn tree_count_by_stage(&self) -> [usize; std::mem::variant_count::<TreeGrowthStage>()] {
+        let mut result = [0; std::mem::variant_count::<TreeGrowthStage>()];
+
+        for (_, tree) in self.iter_all_trees() {
+            result[tree.stage as usize] += 1;
+        }
+
+        result
+    }
+
+    /// Scale sim time by `scale` for the next `duration` of (unscaled) time.
+    pub fn slow_for(&mut self, duration: Duration, scale: f32) {
+        self.slow_motion = Some(SlowMotion { remaining: duration, scale });
+    }
+
+    /// Returns the current time scale, counting down any active slow motion by dt.
+    fn advance_slow_motion(&mut self, dt: Duration) -> f32 {
+        match self.slow_motion.as_mut() {
+            Some(slow_motion) => {
+                let scale = slow_motion.scale;
+
+                slow_motion.remaining = slow_motion.remaining.saturating_sub(dt);
+                if slow_motion.remaining.is_zero() {
+                    self.slow_motion = None;
+                }
+
+                scale
+            },
+            None => 1.0,
+        }
+    }
+
+    /// Returns the number of trees killed this tick.
+    fn update_trees(&mut self, dt_s: f32) -> usize {
+        let max_num_events = self.trees.len();
 
         let mut count_events = 0;
-        let mut tree_events = [MaybeUninit::uninit(); MAX_NUM_EVENTS];
+        let mut tree_events = vec![MaybeUninit::uninit(); max_num_events];
 
         #[derive(Copy, Clone, Debug)]
         enum Event {
-            Plant { pos: WorldPosition, species: TreeSpecies },
+            Plant { pos: WorldPosition, species: TreeSpecies, parent: WorldPosition },
             Kill { tree_slot_index: usize },
-            Delete { tree_slot_index: usize }
+            Delete { tree_slot_index: usize },
+            Ignite { tree_slot_index: usize },
         }
 
         macro_rules! push_event {
             ($e:expr) => {
-                if count_events < MAX_NUM_EVENTS {
+                if count_events < max_num_events {
                     tree_events.get_mut(count_events).unwrap().write($e);
                     count_events += 1;
                 }
             };
         }
 
+        let season = self.current_season();
+        let season_growth_multiplier = season.map_or(1.0, |season| season.growth_multiplier());
+        let seeding_allowed = season.is_none_or(|season| season.allows_seeding());
+        let fire_spread_chance = f32::min(FIRE_SPREAD_RATE * dt_s, 1.0) as f64;
+        let mut fire_candidates = Vec::new();
+
         let mut tile_index = 0;
-        while tile_index < GRID_SIZE {
+        while tile_index < self.grid_size() {
             // SAFETY:
-            //  tile_index ranging from 0..GRID_SIZE, and every tile has a corresponding tree count.
+            //  tile_index ranging from 0..grid_size, and every tile has a corresponding tree count.
             let num_trees_on_tile = {
                 *(unsafe { self.per_tile_tree_count.get_unchecked(tile_index) }) as usize
             };
 
             // SAFETY:
-            //  tile_index ranging from 0..GRID_SIZE.
+            //  tile_index ranging from 0..grid_size.
             let soil_type = unsafe { self.tiles.get_unchecked(tile_index).1 };
 
             let mut tree_index = 0;
@@ -517,7 +1610,9 @@ impl GameState {
                 //  tree_index must be < num_trees_on_tile
                 let tree = unsafe { self.trees.get_unchecked_mut(slot_index).as_mut().unwrap_unchecked() };
 
-                let soil_multiplier = if soil_type == tree.species.soil_preference() { 1.0 } else { 0.4 };
+                tree.age_s += dt_s;
+
+                let soil_multiplier = if soil_type == tree.species.soil_preference() { 1.0 } else { SOIL_MISMATCH_GROWTH_MULTIPLIER };
 
                 let old_shade_factor = tree.shade_factor;
 
@@ -527,14 +1622,30 @@ impl GameState {
                     growth_multiplier *= tree.shade_factor * soil_multiplier;
                 }
 
-                if growth_multiplier <= 0.05 {
+                if growth_multiplier <= MIN_GROWTH_MULTIPLIER {
                     push_event!(Event::Kill { tree_slot_index: slot_index });
                     tree_index += 1;
                     continue;
                 };
 
                 let old_grow_stage = tree.stage;
-                let new_grow_stage = tree.grow(dt_s * growth_multiplier);
+                // Past its lifespan a tree declines no matter how well it's growing, picked up as a stage change below.
+                if tree.is_alive() && tree.age_s >= self.species_config.max_age_s(tree.species) {
+                    tree.age_out(&self.species_config);
+                }
+                // Applied after the kill check, these speed up or slow growth but never starve a tree.
+                if tree.is_alive() {
+                    growth_multiplier *= self.edge_growth.map_or(1.0, |rule| rule.multiplier_at(tree.position, self.grid_dim));
+                    growth_multiplier *= season_growth_multiplier;
+                }
+
+                // Scaling the growth rate down is equivalent to scaling every stage's growth requirement up.
+                let seed_timer = tree.seed_timer;
+                let new_grow_stage = tree.grow(dt_s * growth_multiplier / self.growth_scale, &self.species_config);
+                // The seed timer holds through winter, otherwise every mature tree comes out of it ready to seed at once.
+                if !seeding_allowed {
+                    tree.seed_timer = seed_timer;
+                }
                 drop(tree);
 
                 if (old_grow_stage != new_grow_stage) {
@@ -545,8 +1656,8 @@ impl GameState {
                         let t_ref = self.trees.get(slot_index).unwrap().as_ref().unwrap();
                         let tree_pos = t_ref.position;
                         let tree_species = t_ref.species;
-                        let old_shadow_radius = tree_species.shadow_radius(old_grow_stage);
-                        let new_shadow_radius = tree_species.shadow_radius(new_grow_stage);
+                        let old_shadow_radius = self.species_config.shadow_radius(tree_species, old_grow_stage);
+                        let new_shadow_radius = self.species_config.shadow_radius(tree_species, new_grow_stage);
                         let max_shadow_radius = f32::max(old_shadow_radius, new_shadow_radius);
 
                         let slots = self.iter_trees_in_radius_mut(tree_pos, max_shadow_radius).map(|(i, _)| i).collect::<Vec<_>>();
@@ -571,14 +1682,17 @@ impl GameState {
                             _ => 0.0,
                         };
 
-                        if tree.seed_timer <= 0.0 {
+                        if seeding_allowed && tree.seed_timer <= 0.0 {
                             let numerator = 1;
                             let mut denominator = (10.0 * (1.0 / seed_multiplier)) as u32;
                             if soil_type != tree.species.soil_preference() { denominator *= 2; }
+                            // Shaded, stressed parents produce fewer viable seeds.
+                            let vigor = tree.shade_factor.clamp(MIN_GROWTH_MULTIPLIER, 1.0);
+                            denominator = (denominator as f32 / vigor).round() as u32;
 
                             for _ in 0..3 {
                                 if self.rng.gen_ratio(numerator, denominator) {
-                                    let (min_r, max_r) = tree.species.seed_radius();
+                                    let (min_r, max_r) = self.species_config.seed_radius(tree.species);
 
                                     let angle: f32 = {
                                         let deg: f32 = self.rng.gen_range(0.0..360.0);
@@ -586,25 +1700,24 @@ impl GameState {
                                     };
                                     let radius: f32 = self.rng.gen_range(min_r..=max_r);
 
-                                    let x = radius * angle.cos();
-                                    let y = radius * angle.sin();
-
-                                    let plant_position = tree.position + TileOffset { x, y };
-
-                                    // Fail to plant if we're oob.
-                                    if plant_position.coord.x < GRID_DIM as i32 && plant_position.coord.x >= 0 &&
-                                       plant_position.coord.y < GRID_DIM as i32 && plant_position.coord.y >= 0
-                                    {
-                                        push_event!(
-                                            Event::Plant {
-                                                pos: plant_position,
-                                                species: tree.species,
-                                            }
-                                        );
+                                    let landing_position = tree.position + TileOffset::from_angle(angle) * radius;
+
+                                    // Seeds landing off the grid are dropped, reflected or wrapped.
+                                    if let Some(plant_position) = self.edge_policy.apply(landing_position, self.grid_dim) {
+                                        // Seeds landing on a full tile roll over into a neighbor with room instead of being wasted.
+                                        if let Some(coord) = tile_with_room(&self.per_tile_tree_count, self.grid_dim, plant_position.coord) {
+                                            push_event!(
+                                                Event::Plant {
+                                                    pos: WorldPosition { coord, offset: plant_position.offset },
+                                                    species: tree.species,
+                                                    parent: tree.position,
+                                                }
+                                            );
+                                        }
                                     }
 
                                     tree.seed_timer = {
-                                        let seed_rate = tree.species.seed_success_rate();
+                                        let seed_rate = self.species_config.seed_success_rate(tree.species);
                                         let min = seed_rate.average - seed_rate.variation;
                                         let max = seed_rate.average + seed_rate.variation;
 
@@ -622,13 +1735,42 @@ impl GameState {
                     _ => {}
                 }
 
+                if tree.burning {
+                    tree.burn_timer -= dt_s;
+                    let burnt_out = tree.burn_timer <= 0.0;
+                    let tree_pos = tree.position;
+                    let spread_radius = self.species_config.shadow_radius(tree.species, tree.stage);
+
+                    // Fire only jumps between trees close enough to touch, open ground acts as a firebreak.
+                    fire_candidates.clear();
+                    fire_candidates.extend(
+                        self.iter_living_trees_in_radius(tree_pos, spread_radius)
+                            .filter(|&(index, t)| index != slot_index && !t.burning)
+                            .map(|(index, _)| index)
+                    );
+
+                    for &candidate in fire_candidates.iter() {
+                        if self.rng.gen_bool(fire_spread_chance) {
+                            push_event!(Event::Ignite { tree_slot_index: candidate });
+                        }
+                    }
+
+                    if burnt_out {
+                        push_event!(Event::Kill { tree_slot_index: slot_index });
+                    }
+                }
+
                 tree_index += 1;
             }
 
             tile_index += 1;
         }
 
-        let mut tiles_to_repack = std::collections::HashSet::<usize>::new();
+        let mut count_killed = 0;
+        let mut count_living = self.max_living_trees.map(|_| self.count_living_trees());
+
+        // NOTE: Ordered set, HashSet iteration order changes run to run which breaks determinism.
+        let mut tiles_to_repack = std::collections::BTreeSet::<usize>::new();
 
         for index in 0..count_events {
             // SAFETY:
@@ -636,18 +1778,48 @@ impl GameState {
             let event = unsafe { tree_events.get_unchecked(index).assume_init() };
 
             match event {
-                Event::Plant { pos, species } => self.plant_tree(pos, species),
+                Event::Plant { pos, species, parent } => {
+                    if let (Some(living), Some(max_living)) = (count_living, self.max_living_trees) {
+                        if living >= max_living { continue; }
+                    }
+
+                    if self.plant_tree(pos, species).is_ok() {
+                        if let Some(living) = count_living.as_mut() { *living += 1; }
+                        if self.debug.show_seed_dispersal {
+                            self.seed_dispersals.push(SeedDispersal { from: parent, to: pos, age_s: 0.0 });
+                        }
+                        if !self.listeners.is_empty() {
+                            self.listeners.notify(&SimEvent::Planted { position: pos, species });
+                        }
+                    }
+                },
                 Event::Kill { tree_slot_index } => {
+                    if !self.listeners.is_empty() {
+                        let tree = self.trees.get(tree_slot_index).unwrap().as_ref().unwrap();
+                        self.listeners.notify(&SimEvent::Killed { position: tree.position, species: tree.species });
+                    }
+
                     // Not strictly necessary, but we don't know if kill_tree() is going to delete a tree.
-                    tiles_to_repack.insert(tree_slot_index / NUM_TREES_PER_TILE);
+                    tiles_to_repack.insert(tile_index_of_slot!(tree_slot_index));
                     self.kill_tree(tree_slot_index);
+                    count_killed += 1;
+                    if let Some(living) = count_living.as_mut() { *living = living.saturating_sub(1); }
                 },
                 Event::Delete { tree_slot_index } => {
-                    tiles_to_repack.insert(tree_slot_index / NUM_TREES_PER_TILE);
+                    if !self.listeners.is_empty() {
+                        let tree = self.trees.get(tree_slot_index).unwrap().as_ref().unwrap();
+                        self.listeners.notify(&SimEvent::Removed { position: tree.position, species: tree.species });
+                    }
+
+                    tiles_to_repack.insert(tile_index_of_slot!(tree_slot_index));
                     // SAFETY:
                     //  tree_slot_index comes directly from iteration index when updating trees above.
                     unsafe { self.delete_tree(tree_slot_index) }
                 },
+                Event::Ignite { tree_slot_index } => {
+                    // May have died or been deleted by an earlier event this update, ignite_tree skips those.
+                    self.ignite_tree(tree_slot_index);
+                },
             }
         }
 
@@ -657,18 +1829,19 @@ impl GameState {
             //  tree_slot_index comes directly from iteration index when updating trees above.
             unsafe { self.pack_trees(tile_index) };
         }
+
+        count_killed
     }
 
-    fn update_grass(&mut self) {
-        let mut new_grass_state: [(GroundCover, SoilType); GRID_SIZE] = self.tiles;
+    fn update_grass(&mut self, dt_s: f32) {
+        let mut new_grass_state = self.tiles.clone();
 
-        for x in 0..(GRID_DIM as i32) {
-            for y in 0..(GRID_DIM as i32) {
-                let tile_index = tile_index!(x, y);
-                let mut grassy_neighbor_count = 0;
+        for x in 0..(self.grid_dim as i32) {
+            for y in 0..(self.grid_dim as i32) {
+                let tile_index = tile_index!(x, y, self.grid_dim);
 
                 // SAFETY:
-                //  tile_index constructed from : x, y ranging from 0..GRID_DIM
+                //  tile_index constructed from : x, y ranging from 0..grid_dim
                 let light_amt = unsafe { self.iter_trees_on_tile_unchecked(tile_index) }
                     .fold(1.0, |mut acc, tree| {
                         match tree.stage {
@@ -682,7 +1855,7 @@ impl GameState {
                     });
 
                 // SAFETY:
-                //  tile_index constructed from : x, y ranging from 0..GRID_DIM
+                //  tile_index constructed from : x, y ranging from 0..grid_dim
                 unsafe {
                     let tile_light = self.tile_light_amt.get_unchecked_mut(tile_index);
                     *tile_light = light_amt;
@@ -690,31 +1863,15 @@ impl GameState {
 
                 if light_amt <= 0.25 {
                     // SAFETY:
-                    //  tile_index constructed from : x, y ranging from 0..GRID_DIM
+                    //  tile_index constructed from : x, y ranging from 0..grid_dim
                     unsafe {
                         new_grass_state.get_unchecked_mut(tile_index).0 = GroundCover::Dirt;
                     }
                 } else {
                     // SAFETY:
-                    //  tile_index constructed from : x, y ranging from 0..GRID_DIM
+                    //  tile_index constructed from : x, y ranging from 0..grid_dim
                     if let (GroundCover::Dirt, _) = unsafe { self.tiles.get_unchecked(tile_index) } {
-                        for neighbor_x in (x-1)..=(x+1) {
-                            for neighbor_y in (y-1)..=(y+1) {
-                                if neighbor_x == neighbor_y { continue; }
-
-                                if
-                                    (neighbor_x >= 0) && (neighbor_x < GRID_DIM as i32) &&
-                                    (neighbor_y >= 0) && (neighbor_y < GRID_DIM as i32)
-                                {
-                                    let neighbor_index = tile_index!(neighbor_x, neighbor_y);
-                                    if let (GroundCover::Grass, _) = self.tiles.get(neighbor_index).unwrap() {
-                                        grassy_neighbor_count += 1;
-                                    }
-                                }
-                            }
-                        }
-
-                        let growth_chance = match grassy_neighbor_count {
+                        let growth_chance = match self.grassy_neighbor_count(tile_index) {
                             1     => 0.00001,
                             2     => 0.00005,
                             3..=5 => 0.0001,
@@ -725,7 +1882,7 @@ impl GameState {
                         let grow_roll = self.rng.gen_range(0.0..=1.0);
                         if grow_roll > (1.0 - growth_chance) {
                             // SAFETY:
-                            //  tile_index constructed from : x, y ranging from 0..GRID_DIM
+                            //  tile_index constructed from : x, y ranging from 0..grid_dim
                             unsafe {
                                 new_grass_state.get_unchecked_mut(tile_index).0 = GroundCover::Grass;
                             }
@@ -736,16 +1893,196 @@ impl GameState {
             }
         }
 
+        for (tile_index, (old, new)) in self.tiles.iter().zip(new_grass_state.iter()).enumerate() {
+            if old.0 != new.0 {
+                self.changed_tiles.push(tile_index);
+            }
+        }
+
         self.tiles = new_grass_state;
+
+        let transition_amt = GRASS_TRANSITION_RATE * dt_s;
+        for (tile, grass_amt) in self.tiles.iter().zip(self.tile_grass_amt.iter_mut()) {
+            *grass_amt = match tile.0 {
+                GroundCover::Grass => f32::min(*grass_amt + transition_amt, 1.0),
+                GroundCover::Dirt  => f32::max(*grass_amt - transition_amt, 0.0),
+            };
+        }
+    }
+}
+
+/// What GameState::serialize writes.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SavedGameState {
+    grid_dim: usize,
+    tiles: Vec<(GroundCover, SoilType)>,
+    tile_light_amt: Vec<f32>,
+    per_tile_tree_count: Vec<u8>,
+    trees: Vec<Option<Tree>>,
+    count_trees: usize,
+    camera: GameCamera,
+    species_config: SpeciesConfig,
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub enum LoadError {
+    Decode(bincode::Error),
+    // grid_dim is 0 or over MAX_GRID_DIM, or one of the saved arrays doesn't match it.
+    WrongGridSize,
+    // per_tile_tree_count disagrees with the trees packed in the front of a tile's slots, or with count_trees.
+    CorruptTreePacking,
+    // A tree's position is on a different tile than the slot it's saved in.
+    TreeOnWrongTile,
+}
+
+#[cfg(feature = "serde")]
+impl std::fmt::Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Decode(e)          => write!(f, "couldn't decode game state: {}", e),
+            Self::WrongGridSize      => write!(f, "saved tiles don't match the saved grid dimensions"),
+            Self::CorruptTreePacking => write!(f, "tree counts don't match the trees in each tile's slots"),
+            Self::TreeOnWrongTile    => write!(f, "a tree is saved in another tile's slots"),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl std::error::Error for LoadError { }
+
+#[cfg(feature = "serde")]
+impl GameState {
+    pub fn serialize(&self) -> Vec<u8> {
+        let saved = SavedGameState {
+            grid_dim: self.grid_dim,
+            tiles: self.tiles.to_vec(),
+            tile_light_amt: self.tile_light_amt.to_vec(),
+            per_tile_tree_count: self.per_tile_tree_count.to_vec(),
+            trees: self.trees.to_vec(),
+            count_trees: self.count_trees,
+            camera: self.camera.clone(),
+            species_config: self.species_config.clone(),
+        };
+
+        // Serializing plain data into a Vec can't fail.
+        bincode::serialize(&saved).unwrap()
+    }
+
+    /// Everything not in the save (rng, debug flags, timers...) starts fresh, the rng is seeded with 0.
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, LoadError> {
+        let saved: SavedGameState = bincode::deserialize(bytes).map_err(LoadError::Decode)?;
+
+        let grid_size = checked_grid_size(saved.grid_dim).ok_or(LoadError::WrongGridSize)?;
+        if
+            saved.tiles.len() != grid_size ||
+            saved.tile_light_amt.len() != grid_size ||
+            saved.per_tile_tree_count.len() != grid_size ||
+            saved.trees.len() != grid_size * NUM_TREES_PER_TILE
+        {
+            return Err(LoadError::WrongGridSize);
+        }
+
+        let mut result = Self::with_params(0, saved.grid_dim, saved.species_config, &SimParams::default());
+
+        let tiles = saved.tiles.into_boxed_slice();
+        let per_tile_tree_count = saved.per_tile_tree_count.into_boxed_slice();
+        let trees = saved.trees.into_boxed_slice();
+
+        // Everything touching trees assumes they're packed in the front of each tile's slots, check before trusting it.
+        let mut count_trees = 0;
+        for (tile_index, &count) in per_tile_tree_count.iter().enumerate() {
+            let count = count as usize;
+            if count > NUM_TREES_PER_TILE {
+                return Err(LoadError::CorruptTreePacking);
+            }
+
+            let begin = tree_slot_index!(tile_index, 0);
+            let slots = &trees[begin..(begin + NUM_TREES_PER_TILE)];
+            let (occupied, empty) = slots.split_at(count);
+            if occupied.iter().any(|t| t.is_none()) || empty.iter().any(|t| t.is_some()) {
+                return Err(LoadError::CorruptTreePacking);
+            }
+
+            let x = (tile_index % saved.grid_dim) as i32;
+            let y = (tile_index / saved.grid_dim) as i32;
+            if occupied.iter().flatten().any(|tree| tree.position.coord.x != x || tree.position.coord.y != y) {
+                return Err(LoadError::TreeOnWrongTile);
+            }
+
+            count_trees += count;
+        }
+
+        if count_trees != saved.count_trees {
+            return Err(LoadError::CorruptTreePacking);
+        }
+
+        result.tile_grass_amt = tiles.iter().map(|tile| if tile.0 == GroundCover::Grass { 1.0 } else { 0.0 }).collect();
+        result.tiles = tiles;
+        result.tile_light_amt = saved.tile_light_amt.into_boxed_slice();
+        result.per_tile_tree_count = per_tile_tree_count;
+        result.trees = trees;
+        result.count_trees = count_trees;
+        result.camera = saved.camera;
+
+        Ok(result)
     }
 }
 
+/// Tiles in a grid_dim x grid_dim world, None for 0 or anything over MAX_GRID_DIM. For sizes read from untrusted input.
+fn checked_grid_size(grid_dim: usize) -> Option<usize> {
+    if grid_dim == 0 || grid_dim > MAX_GRID_DIM { return None; }
+    grid_dim.checked_mul(grid_dim)
+}
+
+fn random_logged_seed() -> u64 {
+    let seed: u64 = rand::thread_rng().gen();
+    log::debug!("GameState seed: {}", seed);
+    seed
+}
+
 impl Default for GameState {
     fn default() -> Self {
         Self::new()
     }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AgeStats {
+    pub count: usize,
+    pub mean_s: f32,
+    pub median_s: f32,
+    pub max_s: f32,
+
+    // Bin i holds trees aged [i, i+1) * HISTOGRAM_BIN_WIDTH_S, the last bin also holds everything older.
+    pub histogram: [usize; AgeStats::HISTOGRAM_BINS],
+}
+
+impl AgeStats {
+    pub const HISTOGRAM_BINS: usize = 10;
+    pub const HISTOGRAM_BIN_WIDTH_S: f32 = 60.0;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlantError {
+    OutOfBounds,
+    TileFull,
+    TooClose,
+}
+
+impl std::fmt::Display for PlantError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OutOfBounds => write!(f, "position is outside the grid"),
+            Self::TileFull    => write!(f, "tile already holds {} trees", NUM_TREES_PER_TILE),
+            Self::TooClose    => write!(f, "position is too close to an existing tree"),
+        }
+    }
+}
+
+impl std::error::Error for PlantError { }
+
 pub struct Input {
     pub t: std::time::Duration,
     pub dt: std::time::Duration,
@@ -762,6 +2099,42 @@ pub struct Input {
     pub show_grid: bool,
     pub show_dual: bool,
     pub show_trees: bool,
+    pub hide_apron: bool,
+    pub show_debug_vectors: bool,
+    pub slow_motion_on_die_off: bool,
+    pub show_slot_indices: bool,
+    pub show_seed_dispersal: bool,
+    pub show_origin: bool,
+
+    // Multiplies simulated time, see GameState::sim_speed.
+    pub sim_speed: f32,
+
+    // Last known cursor position in window coordinates, None when outside the window.
+    pub cursor_position: Option<winit::dpi::PhysicalPosition<f64>>,
+
+    // World position of a left click not yet handled by an update, plants selected_species there.
+    pub left_click: Option<cgmath::Point2<f32>>,
+    pub selected_species: TreeSpecies,
+    // World position of a right click not yet handled by an update, sets the tree under it on fire.
+    pub right_click: Option<cgmath::Point2<f32>>,
+    // World position of a middle click not yet handled by an update, removes the tree nearest it.
+    pub middle_click: Option<cgmath::Point2<f32>>,
+}
+
+impl Input {
+    pub const MAX_SIM_SPEED: f32 = 16.0;
+    const MIN_RUNNING_SIM_SPEED: f32 = 0.25;
+
+    /// Doubles sim_speed, starting back up from a freeze at the slowest running speed.
+    pub fn speed_up(&mut self) {
+        self.sim_speed = if self.sim_speed <= 0.0 { Self::MIN_RUNNING_SIM_SPEED } else { (self.sim_speed * 2.0).min(Self::MAX_SIM_SPEED) };
+    }
+
+    /// Halves sim_speed, freezing once it drops below the slowest running speed.
+    pub fn slow_down(&mut self) {
+        let halved = self.sim_speed * 0.5;
+        self.sim_speed = if halved < Self::MIN_RUNNING_SIM_SPEED { 0.0 } else { halved };
+    }
 }
 
 impl Default for Input {
@@ -780,6 +2153,942 @@ impl Default for Input {
             show_grid: false,
             show_dual: false,
             show_trees: true,
+            hide_apron: false,
+            show_debug_vectors: false,
+            slow_motion_on_die_off: false,
+            show_slot_indices: false,
+            show_seed_dispersal: false,
+            show_origin: false,
+
+            sim_speed: 1.0,
+
+            cursor_position: None,
+
+            left_click: None,
+            selected_species: TreeSpecies::Ash,
+            right_click: None,
+            middle_click: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bare_state(grid_dim: usize) -> GameState {
+        GameState::with_params(1, grid_dim, SpeciesConfig::default(), &SimParams::default())
+    }
+
+    fn pos(x: i32, y: i32, offset_x: f32, offset_y: f32) -> WorldPosition {
+        WorldPosition { coord: TileCoordinate { x, y }, offset: TileOffset { x: offset_x, y: offset_y } }
+    }
+
+    #[test]
+    fn grass_fades_in_over_several_updates() {
+        let mut state = bare_state(3);
+        state.tile_grass_amt[4] = 0.0;
+
+        let mut amounts = Vec::new();
+        for _ in 0..5 {
+            state.step(Duration::from_millis(500));
+            amounts.push(state.tile_grass_amt[4]);
+        }
+
+        assert_eq!(amounts, [0.25, 0.5, 0.75, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn seeded_runs_are_deterministic() {
+        let run = || {
+            let mut state = GameState::new_seeded_with_dimensions(42, 12);
+            state.run(600, Duration::from_secs(1));
+            state
+        };
+        let (a, b) = (run(), run());
+
+        // Slot indices pin down the order tiles were repacked in, the checksum covers the rest.
+        let slots = |state: &GameState| state.iter_all_trees().map(|(slot_index, _)| slot_index).collect::<Vec<_>>();
+        assert_eq!(slots(&a), slots(&b));
+        assert_eq!(a.checksum(), b.checksum());
+        assert_ne!(a.checksum(), GameState::new_seeded_with_dimensions(42, 12).checksum());
+    }
+
+    #[test]
+    fn world_to_tile_rejects_points_off_the_grid() {
+        let state = GameState::new_seeded_with_dimensions(1, 4);
+        let max = 4.0 * TILE_DIM;
+        let tile = |x: f32, y: f32| state.world_to_tile(cgmath::Point2::new(x, y)).map(|t| (t.x, t.y));
+
+        assert_eq!(tile(0.0, 0.0), Some((0, 0)));
+        assert_eq!(tile(TILE_DIM * 1.5, TILE_DIM * 2.5), Some((1, 2)));
+        assert_eq!(tile(max - 0.01, max - 0.01), Some((3, 3)));
+
+        assert_eq!(tile(-0.01, 0.0), None);
+        assert_eq!(tile(0.0, -TILE_RAD * 0.5), None);
+        assert_eq!(tile(max, 0.0), None);
+        assert_eq!(tile(0.0, max + TILE_RAD * 0.5), None);
+        assert_eq!(tile(f32::NAN, 0.0), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_round_trips() {
+        let state = GameState::new_seeded_with_dimensions(7, 12);
+        let loaded = GameState::deserialize(&state.serialize()).unwrap();
+
+        assert_eq!(loaded.grid_dim(), 12);
+        assert_eq!(loaded.count_trees, state.count_trees);
+        assert_eq!(loaded.checksum(), state.checksum());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_rejects_an_oversized_grid() {
+        let mut bytes = GameState::new_seeded_with_dimensions(7, 4).serialize();
+        // grid_dim is the first field, a little endian u64.
+        bytes[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+
+        assert!(matches!(GameState::deserialize(&bytes), Err(LoadError::WrongGridSize)));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_rejects_a_tree_on_another_tiles_slot() {
+        let mut state = GameState::new_seeded_with_dimensions(7, 8);
+        let (slot, _) = state.iter_all_trees().next().unwrap();
+        let tree = state.trees[slot].as_mut().unwrap();
+        tree.position.coord.x = (tree.position.coord.x + 1) % 8;
+
+        assert!(matches!(GameState::deserialize(&state.serialize()), Err(LoadError::TreeOnWrongTile)));
+    }
+
+    #[test]
+    fn tree_slot_for_position_finds_the_tree_near_a_click() {
+        let mut state = bare_state(4);
+        let slot = state.try_plant_tree(pos(2, 1, 0.3, 0.6), TreeSpecies::Ash).unwrap();
+
+        assert_eq!(state.tree_slot_for_position(pos(2, 1, 0.32, 0.58), 0.1), Some(slot));
+        assert_eq!(state.tree_slot_for_position(pos(2, 1, 0.8, 0.1), 0.1), None);
+        assert_eq!(state.tree_slot_for_position(pos(0, 0, 0.3, 0.6), 0.1), None);
+        assert_eq!(state.tree_slot_for_position(pos(9, 1, 0.3, 0.6), 0.1), None);
+    }
+
+    #[test]
+    fn killed_trees_stop_counting_as_living() {
+        let mut state = bare_state(4);
+        let slots = [pos(0, 0, 0.5, 0.5), pos(2, 0, 0.5, 0.5), pos(0, 2, 0.5, 0.5)]
+            .map(|p| state.plant_grown_tree(p, TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap());
+
+        state.kill_tree(slots[0]);
+        state.kill_tree(slots[2]);
+
+        assert_eq!(state.count_trees, 3);
+        assert_eq!(state.count_living_trees(), 1);
+    }
+
+    #[test]
+    fn slow_motion_scales_dt_until_it_runs_out() {
+        let mut state = bare_state(2);
+        state.slow_for(Duration::from_secs(1), 0.1);
+
+        let mut advanced = Vec::new();
+        for _ in 0..4 {
+            let before = state.elapsed();
+            state.step(Duration::from_millis(500));
+            advanced.push((state.elapsed() - before).as_secs_f32());
+        }
+
+        for (advanced, expected) in advanced.into_iter().zip([0.05, 0.05, 0.5, 0.5]) {
+            assert!((advanced - expected).abs() < 1e-4, "advanced {} expected {}", advanced, expected);
+        }
+    }
+
+    #[test]
+    fn doubling_growth_scale_doubles_the_time_to_mature() {
+        let ticks_to_mature = |growth_scale: f32| {
+            let params = SimParams { growth_scale, ..SimParams::default() };
+            let mut state = GameState::with_params(1, 3, SpeciesConfig::default(), &params);
+            let slot = state.try_plant_tree(pos(1, 1, 0.5, 0.5), TreeSpecies::Ash).unwrap();
+
+            let mut ticks = 0;
+            while state.trees[slot].as_ref().unwrap().stage != TreeGrowthStage::Mature {
+                state.step(Duration::from_millis(100));
+                ticks += 1;
+            }
+            ticks
+        };
+
+        let (normal, doubled) = (ticks_to_mature(1.0), ticks_to_mature(2.0));
+        assert!((doubled as f32 / normal as f32 - 2.0).abs() < 0.05, "{} vs {} ticks", normal, doubled);
+    }
+
+    #[test]
+    fn canopy_coverage_runs_from_bare_to_forested() {
+        let mut state = bare_state(3);
+        state.update_grass(0.0);
+        assert_eq!(state.canopy_coverage(), 0.0);
+
+        for tile_index in 0..state.grid_size() {
+            let (x, y) = ((tile_index % 3) as i32, (tile_index / 3) as i32);
+            for i in 0..NUM_TREES_PER_TILE {
+                state.plant_grown_tree(pos(x, y, 0.05 + 0.09 * i as f32, 0.5), TreeSpecies::Oak, TreeGrowthStage::Mature).unwrap();
+            }
+        }
+        state.update_grass(0.0);
+        assert!(state.canopy_coverage() > 0.99);
+    }
+
+    #[test]
+    fn clones_advance_independently() {
+        let original = GameState::new_seeded_with_dimensions(3, 8);
+        let checksum = original.checksum();
+
+        let mut branch = original.clone();
+        branch.run(50, Duration::from_secs(1));
+
+        assert_eq!(original.checksum(), checksum);
+        assert_eq!(original.tick_count, 0);
+        assert_ne!(branch.checksum(), checksum);
+    }
+
+    #[test]
+    fn a_single_grass_flip_records_one_changed_tile() {
+        let mut state = bare_state(3);
+        // Two mature canopies on the centre tile shade it down to 0.25 light, the grass dies back.
+        state.plant_grown_tree(pos(1, 1, 0.2, 0.2), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+        state.plant_grown_tree(pos(1, 1, 0.8, 0.8), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+
+        state.update_grass(0.1);
+
+        assert_eq!(state.changed_tiles(), &[tile_index!(1, 1, 3)]);
+    }
+
+    #[test]
+    fn run_advances_one_tick_per_step() {
+        let mut state = bare_state(4);
+        state.run(100, Duration::from_millis(16));
+        assert_eq!(state.tick_count, 100);
+
+        state.run(5, Duration::from_millis(16));
+        assert_eq!(state.tick_count, 105);
+    }
+
+    #[test]
+    fn living_tree_cap_stops_seeding() {
+        let seeded = |max_living_trees| {
+            let params = SimParams { max_living_trees, ..SimParams::default() };
+            let mut state = GameState::with_params(1, 8, SpeciesConfig::default(), &params);
+            state.plant_grown_tree(pos(4, 4, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+            state.run(600, Duration::from_millis(100));
+            state.count_living_trees()
+        };
+
+        assert!(seeded(None) > 1);
+        assert_eq!(seeded(Some(1)), 1);
+    }
+
+    #[test]
+    fn changing_species_retargets_growth_and_sprite() {
+        use crate::game::TileType;
+
+        let mut state = bare_state(4);
+        let slot = state.plant_grown_tree(pos(1, 1, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+        let stage_begin = {
+            let tree = state.trees[slot].as_ref().unwrap();
+            tree.growth_target.unwrap() - state.species_config.growth_required(TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap()
+        };
+
+        state.change_species(slot, TreeSpecies::Fir);
+
+        let tree = state.trees[slot].as_ref().unwrap();
+        let fir_required = state.species_config.growth_required(TreeSpecies::Fir, TreeGrowthStage::Mature).unwrap();
+        assert_eq!(tree.growth_target, Some(stage_begin + fir_required));
+        assert_eq!(TileType::from(tree), TileType::PineTreeMature);
+    }
+
+    #[test]
+    fn light_map_is_dark_under_canopy_and_bright_in_the_open() {
+        let mut state = bare_state(3);
+        for offset in [0.2, 0.5, 0.8] {
+            state.plant_grown_tree(pos(0, 0, offset, offset), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+        }
+        state.update_grass(0.1);
+
+        let light_map = state.export_light_map();
+        // Rows are flipped, tile (0, 0) is the bottom left pixel.
+        let shaded = light_map.get_pixel(0, 2)[0];
+        let open = light_map.get_pixel(2, 0)[0];
+        assert!(shaded < 64, "shaded pixel {}", shaded);
+        assert_eq!(open, 255);
+    }
+
+    #[test]
+    fn listeners_see_seedlings_planted_during_update() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+        use crate::game::events::{EventListener, SimEvent};
+
+        struct PlantCounter(Rc<Cell<usize>>);
+        impl EventListener for PlantCounter {
+            fn on_event(&mut self, event: &SimEvent) {
+                if let SimEvent::Planted { .. } = event { self.0.set(self.0.get() + 1); }
+            }
+        }
+
+        let mut state = bare_state(8);
+        state.plant_grown_tree(pos(4, 4, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+
+        let planted = Rc::new(Cell::new(0));
+        state.add_event_listener(Box::new(PlantCounter(planted.clone())));
+        state.run(600, Duration::from_millis(100));
+
+        assert!(planted.get() > 0);
+        assert_eq!(planted.get(), state.count_trees - 1);
+    }
+
+    #[test]
+    fn stony_soil_slows_a_normal_soil_tree_next_tick() {
+        let mut state = bare_state(3);
+        let slot = state.plant_grown_tree(pos(1, 1, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap();
+        let growth = |state: &GameState| state.trees[slot].as_ref().unwrap().growth;
+
+        let before = growth(&state);
+        state.update_trees(0.1);
+        let normal_growth = growth(&state) - before;
+
+        assert!(state.set_soil(1, 1, SoilType::Stony));
+        let before = growth(&state);
+        state.update_trees(0.1);
+        let stony_growth = growth(&state) - before;
+
+        assert!(normal_growth > 0.0);
+        assert!((stony_growth - normal_growth * SOIL_MISMATCH_GROWTH_MULTIPLIER).abs() < 1e-5);
+        assert!(!state.set_soil(3, 0, SoilType::Stony));
+    }
+
+    #[test]
+    fn seeds_landing_on_a_full_tile_move_next_door() {
+        let grid_dim = 3;
+        let mut counts = vec![0u8; grid_dim * grid_dim];
+        let room = |counts: &[u8]| {
+            tile_with_room(counts, grid_dim, TileCoordinate { x: 1, y: 1 }).map(|coord| (coord.x, coord.y))
+        };
+
+        assert_eq!(room(&counts), Some((1, 1)));
+
+        counts[tile_index!(1, 1, grid_dim)] = NUM_TREES_PER_TILE as u8;
+        assert_eq!(room(&counts), Some((0, 1)));
+
+        // Nowhere to go, the seed is dropped.
+        counts.fill(NUM_TREES_PER_TILE as u8);
+        assert_eq!(room(&counts), None);
+    }
+
+    #[test]
+    fn fit_to_grid_shows_the_whole_grid_at_16_9() {
+        let aspect_ratio = 16.0 / 9.0;
+        for grid_dim in [10, 30, 100] {
+            let camera = GameCamera::framed(CameraFraming::FitToGrid { aspect_ratio }, grid_dim);
+            let grid_extent = grid_dim as f32 * TILE_DIM;
+
+            // zoom_level is the visible height, the width follows from the aspect ratio.
+            assert!(camera.zoom_level >= grid_extent);
+            assert!(camera.zoom_level * aspect_ratio >= grid_extent);
+            assert_eq!((camera.position.x, camera.position.y), (grid_extent * 0.5, grid_extent * 0.5));
+        }
+    }
+
+    #[test]
+    fn clicks_plant_where_they_land_until_the_tile_is_full() {
+        let mut state = bare_state(4);
+        state.plant_at_click(cgmath::Point2::new(2.3, 1.6), TreeSpecies::Fir);
+
+        let (_, tree) = state.iter_all_trees().next().unwrap();
+        assert_eq!((tree.position.coord.x, tree.position.coord.y), (2, 1));
+        assert!((tree.position.offset.x - 0.3).abs() < 1e-4 && (tree.position.offset.y - 0.6).abs() < 1e-4);
+        assert_eq!(tree.species, TreeSpecies::Fir);
+
+        for i in 0..NUM_TREES_PER_TILE {
+            let offset = 0.05 + i as f32 * 0.09;
+            state.try_plant_tree(pos(0, 0, offset, offset), TreeSpecies::Ash).unwrap();
+        }
+        assert_eq!(state.try_plant_tree(pos(0, 0, 0.5, 0.1), TreeSpecies::Ash), Err(PlantError::TileFull));
+    }
+
+    #[test]
+    fn age_stats_cover_the_living_trees() {
+        let mut state = bare_state(4);
+        let slots = [0, 1, 2, 3].map(|x| state.plant_grown_tree(pos(x, 0, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap());
+        for (slot, age_s) in slots.iter().zip([30.0, 90.0, 150.0, 1000.0]) {
+            state.trees[*slot].as_mut().unwrap().age_s = age_s;
+        }
+        state.kill_tree(slots[3]);
+
+        let stats = state.age_stats();
+        assert_eq!(stats.count, 3);
+        assert_eq!((stats.mean_s, stats.median_s, stats.max_s), (90.0, 90.0, 150.0));
+        assert_eq!(stats.histogram[..3], [1, 1, 1]);
+
+        // A running forest ages along with the sim.
+        let mut forest = GameState::new_seeded_with_dimensions(1, 10);
+        forest.run(300, Duration::from_millis(100));
+        let stats = forest.age_stats();
+        assert_eq!(stats.count, forest.count_living_trees());
+        assert_eq!(stats.histogram.iter().sum::<usize>(), stats.count);
+        assert!(stats.mean_s <= stats.max_s && stats.median_s <= stats.max_s);
+    }
+
+    #[test]
+    fn reseeding_picks_the_random_stream() {
+        let base = GameState::new_seeded_with_dimensions(1, 10);
+        let advanced = |seed| {
+            let mut state = base.clone();
+            state.reseed_rng(seed);
+            state.run(300, Duration::from_millis(100));
+            state.checksum()
+        };
+
+        assert_eq!(advanced(5), advanced(5));
+        assert_ne!(advanced(5), advanced(6));
+    }
+
+    #[test]
+    fn stony_soil_in_good_light_suits_fir() {
+        let mut state = bare_state(3);
+        state.set_soil(1, 1, SoilType::Stony);
+
+        let suited = state.suitable_species(1, 1);
+        assert!(suited.contains(&TreeSpecies::Fir));
+
+        // In full light the mismatch penalty alone isn't fatal.
+        assert_eq!(state.suitable_species(0, 0).len(), TreeSpecies::ALL.len());
+
+        // Shaded down, the penalty rules out the normal soil species while Fir still gets by.
+        state.tile_light_amt[tile_index!(1, 1, 3)] = 0.1;
+        let suited = state.suitable_species(1, 1);
+        assert_eq!(suited, vec![TreeSpecies::Fir]);
+
+        assert!(state.suitable_species(3, 0).is_empty());
+    }
+
+    #[test]
+    fn grids_size_themselves_at_runtime() {
+        for grid_dim in [2, 7, 40] {
+            let mut state = GameState::new_seeded_with_dimensions(3, grid_dim);
+            state.run(20, Duration::from_millis(100));
+
+            assert_eq!(state.grid_dim(), grid_dim);
+            assert_eq!(state.grid_size(), grid_dim * grid_dim);
+            assert_eq!(state.export_light_map().dimensions(), (grid_dim as u32, grid_dim as u32));
+            assert!(state.iter_all_trees().all(|(_, tree)| {
+                let coord = tree.position.coord;
+                (0..grid_dim as i32).contains(&coord.x) && (0..grid_dim as i32).contains(&coord.y)
+            }));
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "count_trees is 2")]
+    fn desynced_tree_counts_trip_the_assertion() {
+        let mut state = bare_state(3);
+        state.plant_grown_tree(pos(1, 1, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap();
+        state.assert_tree_counts_in_sync();
+
+        state.count_trees += 1;
+        state.run(1, Duration::from_millis(16));
+    }
+
+    #[test]
+    fn iter_all_trees_walks_every_tile_in_order() {
+        let mut state = bare_state(3);
+        for (x, y, offset) in [(2, 2, 0.2), (0, 0, 0.5), (2, 2, 0.7), (1, 0, 0.5)] {
+            state.plant_grown_tree(pos(x, y, offset, offset), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap();
+        }
+
+        let slots = state.iter_all_trees().map(|(slot, _)| slot).collect::<Vec<_>>();
+        let n = NUM_TREES_PER_TILE;
+        assert_eq!(slots, vec![0, n, 8 * n, 8 * n + 1]);
+
+        for (_, tree) in state.iter_all_trees_mut() {
+            tree.age_s = 5.0;
         }
+        assert!(state.iter_all_trees().all(|(_, tree)| tree.age_s == 5.0));
+    }
+
+    #[test]
+    fn edge_boost_grows_edge_trees_faster() {
+        let params = SimParams { edge_growth: Some(EdgeGrowth { distance: 1.0, multiplier: 2.0 }), ..SimParams::default() };
+        let mut state = GameState::with_params(1, 6, SpeciesConfig::default(), &params);
+        let edge = state.plant_grown_tree(pos(0, 3, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap();
+        let interior = state.plant_grown_tree(pos(3, 3, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap();
+        let growth = |state: &GameState, slot: usize| state.trees[slot].as_ref().unwrap().growth;
+        let (edge_before, interior_before) = (growth(&state, edge), growth(&state, interior));
+
+        for _ in 0..5 {
+            state.update_trees(0.1);
+        }
+
+        let edge_growth = growth(&state, edge) - edge_before;
+        let interior_growth = growth(&state, interior) - interior_before;
+        assert!((edge_growth - interior_growth * 2.0).abs() < 1e-4, "edge {} interior {}", edge_growth, interior_growth);
+    }
+
+    #[test]
+    fn living_radius_query_skips_stumps() {
+        let mut state = bare_state(3);
+        let living = state.plant_grown_tree(pos(1, 1, 0.3, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap();
+        state.plant_grown_tree(pos(1, 1, 0.7, 0.5), TreeSpecies::Ash, TreeGrowthStage::Stump).unwrap();
+
+        let center = pos(1, 1, 0.5, 0.5);
+        assert_eq!(state.iter_trees_in_radius(center, 1.0).count(), 2);
+        let slots = state.iter_living_trees_in_radius(center, 1.0).map(|(slot, _)| slot).collect::<Vec<_>>();
+        assert_eq!(slots, vec![living]);
+    }
+
+    #[test]
+    fn forest_bounds_enclose_a_clustered_stand() {
+        let mut state = bare_state(8);
+        assert!(state.forest_bounds().is_none());
+
+        for (x, y, ox, oy) in [(1, 2, 0.3, 0.6), (2, 1, 0.8, 0.1), (3, 3, 0.5, 0.5)] {
+            state.plant_grown_tree(pos(x, y, ox, oy), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+        }
+        // Dead wood out in the far corner doesn't stretch the bounds.
+        state.plant_grown_tree(pos(7, 7, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Stump).unwrap();
+
+        let (min, max) = state.forest_bounds().unwrap();
+        let world = |p: WorldPosition| (p.coord.x as f32 + p.offset.x, p.coord.y as f32 + p.offset.y);
+        assert_eq!(world(min), (1.3, 1.1));
+        assert_eq!(world(max), (3.5, 3.5));
+    }
+
+    #[test]
+    fn deadwood_is_exactly_the_dead_trees() {
+        let mut state = bare_state(4);
+        let slots = [0, 1, 2, 3].map(|x| state.plant_grown_tree(pos(x, 1, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap());
+        let stump = state.plant_grown_tree(pos(0, 3, 0.5, 0.5), TreeSpecies::Fir, TreeGrowthStage::Stump).unwrap();
+
+        state.kill_tree(slots[1]);
+        state.kill_tree(slots[3]);
+
+        let deadwood = state.iter_deadwood().map(|(slot, _)| slot).collect::<Vec<_>>();
+        assert_eq!(deadwood, vec![slots[1], slots[3], stump]);
+        assert!(state.iter_deadwood().all(|(_, tree)| matches!(tree.stage, TreeGrowthStage::Snag | TreeGrowthStage::Stump)));
+    }
+
+    #[test]
+    fn a_planted_seed_leaves_one_dispersal_marker() {
+        let mut state = bare_state(8);
+        state.debug.show_seed_dispersal = true;
+        let parent = state.plant_grown_tree(pos(4, 4, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+
+        for _ in 0..1000 {
+            if state.count_trees > 1 { break; }
+            state.update_trees(0.1);
+        }
+        assert_eq!(state.count_trees, 2);
+
+        let (_, child) = state.iter_all_trees().find(|&(slot, _)| slot != parent).unwrap();
+        let [marker] = state.seed_dispersals() else { panic!("expected one marker") };
+        let xy = |p: WorldPosition| (p.coord.x, p.coord.y, p.offset.x, p.offset.y);
+        assert_eq!(xy(marker.from), xy(pos(4, 4, 0.5, 0.5)));
+        assert_eq!(xy(marker.to), xy(child.position));
+    }
+
+    #[test]
+    fn compact_repairs_perturbed_counts_and_packing() {
+        let mut state = bare_state(3);
+        for offset in [0.2, 0.5, 0.8] {
+            state.plant_grown_tree(pos(1, 1, offset, offset), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap();
+        }
+        state.plant_grown_tree(pos(0, 2, 0.5, 0.5), TreeSpecies::Fir, TreeGrowthStage::Sapling).unwrap();
+
+        // Punch a hole in tile (1, 1) and scramble the bookkeeping.
+        let tile = tile_index!(1, 1, 3);
+        let first = tree_slot_index!(tile, 0);
+        state.trees[first] = None;
+        state.per_tile_tree_count[tile] = 7;
+        state.count_trees = 42;
+
+        assert_eq!(state.compact(), 3);
+        assert_eq!(state.count_trees, 3);
+        assert_eq!(state.per_tile_tree_count[tile], 2);
+        assert!(state.trees[first..first + 2].iter().all(|t| t.is_some()));
+        assert!(state.trees[first + 2].is_none());
+        #[cfg(debug_assertions)]
+        state.assert_tree_counts_in_sync();
+    }
+
+    #[test]
+    fn fast_forward_climbs_one_stage_per_update() {
+        let mut state = bare_state(3);
+        let slot = state.plant_grown_tree(pos(1, 1, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sprout).unwrap();
+        state.sim_speed = Input::MAX_SIM_SPEED;
+        let stage = |state: &GameState| state.trees[slot].as_ref().unwrap().stage;
+
+        // Enough growth in one step to clear both Sprout and Seedling.
+        state.run(1, Duration::from_millis(500));
+        assert_eq!(stage(&state), TreeGrowthStage::Seedling);
+
+        state.run(1, Duration::from_millis(1));
+        assert_eq!(stage(&state), TreeGrowthStage::Sapling);
+    }
+
+    #[test]
+    fn one_sec_sin_cycles_once_a_second() {
+        let mut state = bare_state(2);
+
+        let mut samples = Vec::new();
+        for _ in 0..4 {
+            state.run(1, Duration::from_millis(250));
+            samples.push(state.one_sec_sin);
+        }
+
+        for (sample, expected) in samples.into_iter().zip([1.0, 0.0, -1.0, 0.0]) {
+            assert!((sample - expected).abs() < 1e-3, "{} expected {}", sample, expected);
+        }
+    }
+
+    #[test]
+    fn ambient_light_follows_the_day() {
+        let params = SimParams { day_length: Some(Duration::from_secs(4)), ..SimParams::default() };
+        let mut state = GameState::with_params(1, 2, SpeciesConfig::default(), &params);
+        assert_eq!(state.ambient_light(), 1.0);
+
+        // Half a day in is midnight.
+        state.run(2, Duration::from_secs(1));
+        assert!((state.ambient_light() - MIN_AMBIENT_LIGHT).abs() < 1e-4);
+
+        state.run(2, Duration::from_secs(1));
+        assert!((state.ambient_light() - 1.0).abs() < 1e-4);
+
+        // No day length, always noon.
+        let mut state = bare_state(2);
+        state.run(2, Duration::from_secs(1));
+        assert_eq!(state.ambient_light(), 1.0);
+    }
+
+    #[test]
+    fn planting_on_top_of_a_tree_is_rejected() {
+        let mut state = bare_state(2);
+        state.plant_tree(pos(1, 1, 0.5, 0.5), TreeSpecies::Ash).unwrap();
+
+        assert!(matches!(state.plant_tree(pos(1, 1, 0.51, 0.5), TreeSpecies::Ash), Err(PlantError::TooClose)));
+        assert!(state.plant_tree(pos(1, 1, 0.2, 0.5), TreeSpecies::Ash).is_ok());
+        // Spacing is per tile, a tree just over the edge of the next tile is fine.
+        state.plant_tree(pos(0, 0, 0.99, 0.99), TreeSpecies::Ash).unwrap();
+        assert!(state.plant_tree(pos(1, 0, 0.0, 0.99), TreeSpecies::Ash).is_ok());
+    }
+
+    #[test]
+    fn cohort_first_seed_times_are_staggered() {
+        let plant_cohort = |params: &SimParams| {
+            let mut state = GameState::with_params(1, 4, SpeciesConfig::default(), params);
+            (0..4)
+                .map(|x| {
+                    let slot = state.plant_tree(pos(x, 0, 0.5, 0.5), TreeSpecies::Ash).unwrap();
+                    state.trees[slot].as_ref().unwrap().seed_timer
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let timers = plant_cohort(&SimParams::default());
+        assert!(timers.windows(2).any(|pair| pair[0] != pair[1]), "{:?}", timers);
+
+        let timers = plant_cohort(&SimParams { seed_timer_jitter: 0.0, ..SimParams::default() });
+        assert!(timers.windows(2).all(|pair| pair[0] == pair[1]), "{:?}", timers);
+    }
+
+    #[test]
+    fn seasons_scale_living_growth_and_hold_seeding_in_winter() {
+        let params = SimParams { season_length: Some(Duration::from_secs(1)), ..SimParams::default() };
+        let mut state = GameState::with_params(1, 4, SpeciesConfig::default(), &params);
+        assert!(bare_state(2).current_season().is_none());
+
+        let seasons = (0..5)
+            .map(|_| {
+                let season = state.current_season().unwrap();
+                state.elapsed += Duration::from_secs(1);
+                season
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(seasons, [Season::Spring, Season::Summer, Season::Autumn, Season::Winter, Season::Spring]);
+
+        let sprout = state.plant_grown_tree(pos(0, 0, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sprout).unwrap();
+        let snag = state.plant_grown_tree(pos(2, 0, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Snag).unwrap();
+        let mature = state.plant_grown_tree(pos(0, 2, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+        state.trees[mature].as_mut().unwrap().seed_timer = 5.0;
+
+        let step = |state: &mut GameState| {
+            let before = [sprout, snag].map(|slot| state.trees[slot].as_ref().unwrap().growth);
+            state.update_trees(0.1);
+            let after = [sprout, snag].map(|slot| state.trees[slot].as_ref().unwrap().growth);
+            [after[0] - before[0], after[1] - before[1]]
+        };
+
+        state.elapsed = Duration::from_millis(500);
+        let [spring_growth, spring_decay] = step(&mut state);
+        state.elapsed = Duration::from_millis(3500);
+        let seed_timer = state.trees[mature].as_ref().unwrap().seed_timer;
+        let [winter_growth, winter_decay] = step(&mut state);
+
+        assert!((winter_growth / spring_growth - Season::Winter.growth_multiplier()).abs() < 1e-3);
+        // Decay isn't living growth, winter doesn't slow it.
+        assert!((winter_decay - spring_decay).abs() < 1e-6);
+        assert_eq!(state.trees[mature].as_ref().unwrap().seed_timer, seed_timer);
+    }
+
+    #[test]
+    fn tile_of_tree_reports_the_planted_tile() {
+        let mut state = bare_state(4);
+        let slot = state.plant_grown_tree(pos(3, 1, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap();
+
+        let tile = state.tile_of_tree(slot).unwrap();
+        assert_eq!((tile.x, tile.y), (3, 1));
+        assert!(state.tile_of_tree(state.trees.len()).is_none());
+    }
+
+    #[test]
+    fn fire_spreads_to_touching_trees_and_burns_out() {
+        let mut state = bare_state(6);
+        let source = state.plant_grown_tree(pos(2, 2, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+        let neighbours = [pos(3, 2, 0.0, 0.5), pos(2, 3, 0.5, 0.0), pos(1, 2, 0.9, 0.5), pos(2, 1, 0.5, 0.9)]
+            .map(|p| state.plant_grown_tree(p, TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap());
+        // Open ground between it and the fire.
+        let far = state.plant_grown_tree(pos(5, 5, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+
+        assert!(state.ignite_tree(source));
+        assert!(!state.ignite_tree(source));
+
+        let mut spread = false;
+        for _ in 0..((BURN_DURATION_S / 0.5) as usize + 1) {
+            state.update_trees(0.5);
+            spread |= neighbours.iter().any(|&slot| state.trees[slot].as_ref().is_some_and(|t| t.burning));
+            assert!(!state.trees[far].as_ref().unwrap().burning);
+        }
+
+        assert!(spread);
+        let source = state.trees[source].as_ref().unwrap();
+        assert!(!source.is_alive());
+        assert!(!source.burning);
+    }
+
+    #[test]
+    fn trees_past_their_lifespan_decline_and_lift_their_shade() {
+        let mut species_config = SpeciesConfig::default();
+        species_config.get_mut(TreeSpecies::Ash).max_age_s = 1.0;
+        let mut state = GameState::with_params(1, 4, species_config, &SimParams::default());
+
+        let old = state.plant_grown_tree(pos(1, 1, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+        state.trees[old].as_mut().unwrap().seed_timer = f32::MAX;
+        // Inside a Mature Ash's shadow but outside a Declining one's.
+        let neighbour = state.plant_grown_tree(pos(2, 1, 0.2, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sapling).unwrap();
+        assert!(state.trees[neighbour].as_ref().unwrap().shade_factor < 1.0);
+
+        state.update_trees(0.5);
+        assert_eq!(state.trees[old].as_ref().unwrap().stage, TreeGrowthStage::Mature);
+
+        state.update_trees(0.6);
+        assert_eq!(state.trees[old].as_ref().unwrap().stage, TreeGrowthStage::Decline);
+        assert!((state.trees[neighbour].as_ref().unwrap().shade_factor - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn stressed_parents_seed_less() {
+        let count_seedings = |shade_factor: f32| {
+            let mut state = bare_state(8);
+            let parent = state.plant_grown_tree(pos(4, 4, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+
+            let mut seedings = 0;
+            for _ in 0..300 {
+                let tree = state.trees[parent].as_mut().unwrap();
+                tree.seed_timer = 0.0;
+                tree.shade_factor = shade_factor;
+
+                state.update_trees(0.01);
+                // The timer only resets once a seed takes.
+                if state.trees[parent].as_ref().unwrap().seed_timer > 0.0 { seedings += 1; }
+            }
+            seedings
+        };
+
+        let healthy = count_seedings(1.0);
+        let stressed = count_seedings(0.2);
+        assert!(stressed * 2 < healthy, "stressed {} vs healthy {}", stressed, healthy);
+    }
+
+    #[test]
+    fn remove_tree_at_takes_the_nearest_tree_and_repacks() {
+        let mut state = bare_state(3);
+        let near = pos(1, 1, 0.4, 0.5);
+        state.plant_grown_tree(pos(1, 1, 0.1, 0.1), TreeSpecies::Ash, TreeGrowthStage::Sprout).unwrap();
+        state.plant_grown_tree(near, TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+        let neighbour = state.plant_grown_tree(pos(1, 1, 0.9, 0.9), TreeSpecies::Ash, TreeGrowthStage::Sprout).unwrap();
+        assert!(state.trees[neighbour].as_ref().unwrap().shade_factor < 1.0);
+
+        assert!(state.remove_tree_at(pos(1, 1, 0.45, 0.5)));
+        assert_eq!(state.per_tile_tree_count[tile_index!(1, 1, 3)], 2);
+        assert!(state.iter_all_trees().all(|(_, tree)| tree.stage == TreeGrowthStage::Sprout));
+        // Packing moved the last tree down, its shade no longer includes the Mature one's.
+        let shade_factors = state.iter_all_trees().map(|(_, tree)| tree.shade_factor).collect::<Vec<_>>();
+        assert_eq!(shade_factors, [1.0, 1.0]);
+        #[cfg(debug_assertions)]
+        state.assert_tree_counts_in_sync();
+
+        assert!(!state.remove_tree_at(pos(0, 0, 0.5, 0.5)));
+        assert!(!state.remove_tree_at(pos(3, 0, 0.5, 0.5)));
+    }
+
+    #[test]
+    fn nearest_tree_measures_across_tile_boundaries() {
+        let mut state = bare_state(3);
+        let same_tile = state.plant_grown_tree(pos(1, 1, 0.5, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sprout).unwrap();
+        let next_tile = state.plant_grown_tree(pos(0, 1, 0.95, 0.5), TreeSpecies::Ash, TreeGrowthStage::Sprout).unwrap();
+
+        // 0.15 to the tree over the tile edge, 0.4 to the one on the same tile.
+        let (slot, _) = state.nearest_tree(pos(1, 1, 0.1, 0.5), 1.0).unwrap();
+        assert_eq!(slot, next_tile);
+        let (slot, _) = state.nearest_tree(pos(1, 1, 0.3, 0.5), 1.0).unwrap();
+        assert_eq!(slot, same_tile);
+
+        assert!(state.nearest_tree(pos(2, 2, 0.9, 0.9), 0.5).is_none());
+    }
+
+    #[test]
+    fn reflect_mirrors_seeds_back_across_the_edge() {
+        let reflected = EdgePolicy::Reflect.apply(pos(-1, 2, 0.7, 0.5), 4).unwrap();
+        assert_eq!((reflected.coord.x, reflected.coord.y), (0, 2));
+        assert!((reflected.offset.x - 0.3).abs() < 1e-5);
+
+        // Past both far edges at once.
+        let reflected = EdgePolicy::Reflect.apply(pos(4, 4, 0.25, 0.5), 4).unwrap();
+        assert_eq!((reflected.coord.x, reflected.coord.y), (3, 3));
+        assert!((reflected.offset.x - 0.75).abs() < 1e-5);
+        assert!((reflected.offset.y - 0.5).abs() < 1e-5);
+
+        let wrapped = EdgePolicy::Wrap.apply(pos(-1, 2, 0.7, 0.5), 4).unwrap();
+        assert_eq!((wrapped.coord.x, wrapped.coord.y), (3, 2));
+        assert!(EdgePolicy::Drop.apply(pos(-1, 2, 0.7, 0.5), 4).is_none());
+
+        // In bounds seeds are left alone whatever the policy.
+        let kept = EdgePolicy::Drop.apply(pos(1, 2, 0.7, 0.5), 4).unwrap();
+        assert_eq!((kept.coord.x, kept.coord.y), (1, 2));
+    }
+
+    #[test]
+    fn reflect_keeps_seeds_from_an_edge_tree_in_bounds() {
+        let params = SimParams { edge_policy: EdgePolicy::Reflect, ..SimParams::default() };
+        let mut state = GameState::with_params(1, 3, SpeciesConfig::default(), &params);
+        let parent = state.plant_grown_tree(pos(0, 0, 0.05, 0.05), TreeSpecies::Ash, TreeGrowthStage::Mature).unwrap();
+
+        for _ in 0..200 {
+            state.trees[parent].as_mut().unwrap().seed_timer = 0.0;
+            state.update_trees(0.01);
+        }
+
+        // Most of a corner tree's seeds land off the grid, Reflect plants them rather than dropping them.
+        assert!(state.count_trees > 1);
+        let extent = state.grid_dim() as f32;
+        for (_, tree) in state.iter_all_trees() {
+            let x = tree.position.coord.x as f32 + tree.position.offset.x;
+            let y = tree.position.coord.y as f32 + tree.position.offset.y;
+            assert!((0.0..extent).contains(&x) && (0.0..extent).contains(&y));
+        }
+    }
+
+    #[test]
+    fn neighbors_stay_on_the_grid_and_skip_the_tile_itself() {
+        let state = bare_state(3);
+        let center = tile_index!(1, 1, 3);
+        let corner = tile_index!(0, 0, 3);
+        let edge = tile_index!(1, 0, 3);
+
+        let mut center_neighbors = state.neighbors(center, true).collect::<Vec<_>>();
+        center_neighbors.sort();
+        assert_eq!(center_neighbors, [0, 1, 2, 3, 5, 6, 7, 8]);
+
+        let mut corner_neighbors = state.neighbors(corner, true).collect::<Vec<_>>();
+        corner_neighbors.sort();
+        assert_eq!(corner_neighbors, [tile_index!(1, 0, 3), tile_index!(0, 1, 3), tile_index!(1, 1, 3)]);
+
+        assert_eq!(state.neighbors(edge, true).count(), 5);
+        assert_eq!(state.neighbors(center, false).count(), 4);
+        assert_eq!(state.neighbors(corner, false).count(), 2);
+    }
+
+    #[test]
+    fn generated_scenario_matches_new_seeded() {
+        let state = GameState::from_scenario(&Scenario::generated(7, 12)).unwrap();
+        assert_eq!(state.checksum(), GameState::new_seeded_with_dimensions(7, 12).checksum());
+    }
+
+    #[test]
+    fn scenario_plantings_go_in_on_top_of_the_terrain() {
+        use super::super::scenario::ScenarioPlanting;
+
+        let mut scenario = Scenario::generated(3, 4);
+        scenario.terrain = ScenarioTerrain::Uniform(SoilType::Stony);
+        scenario.random_trees = false;
+        scenario.plantings.push(ScenarioPlanting { species: TreeSpecies::Fir, position: pos(2, 1, 0.5, 0.5), stage: TreeGrowthStage::Old });
+
+        let state = GameState::from_scenario(&scenario).unwrap();
+        assert!(state.tiles.iter().all(|tile| tile.1 == SoilType::Stony));
+        let trees = state.iter_all_trees().map(|(_, tree)| (tree.species, tree.stage)).collect::<Vec<_>>();
+        assert_eq!(trees, [(TreeSpecies::Fir, TreeGrowthStage::Old)]);
+
+        // The same planting twice lands on top of itself.
+        scenario.plantings.push(scenario.plantings[0]);
+        assert!(matches!(GameState::from_scenario(&scenario), Err(ScenarioError::Planting { index: 1, error: PlantError::TooClose })));
+    }
+
+    #[test]
+    fn scenario_rejects_a_mismatched_grid() {
+        assert!(matches!(GameState::from_scenario(&Scenario::generated(7, MAX_GRID_DIM + 1)), Err(ScenarioError::WrongGridSize)));
+        assert!(matches!(GameState::from_scenario(&Scenario::generated(7, 0)), Err(ScenarioError::WrongGridSize)));
+
+        let mut scenario = Scenario::generated(7, 4);
+        scenario.terrain = ScenarioTerrain::Tiles(vec![(GroundCover::Grass, SoilType::Normal); 15]);
+        assert!(matches!(GameState::from_scenario(&scenario), Err(ScenarioError::WrongGridSize)));
+    }
+
+    #[cfg(feature = "scenario-json")]
+    #[test]
+    fn scenario_json_round_trips() {
+        let mut scenario = Scenario::generated(11, 6);
+        scenario.terrain = ScenarioTerrain::Uniform(SoilType::Stony);
+
+        let loaded = Scenario::from_json(&scenario.to_json()).unwrap();
+        assert_eq!(GameState::from_scenario(&loaded).unwrap().checksum(), GameState::from_scenario(&scenario).unwrap().checksum());
+    }
+
+    #[test]
+    fn grassy_neighbor_count_skips_the_tile_itself() {
+        let mut state = bare_state(4);
+        for tile in state.tiles.iter_mut() {
+            tile.0 = GroundCover::Dirt;
+        }
+
+        // Dirt tile (1, 1) sits on the x == y diagonal, as do two of its grassy neighbours.
+        for (x, y) in [(2, 1), (2, 0), (2, 2), (0, 0)] {
+            state.tiles[tile_index!(x, y, 4)].0 = GroundCover::Grass;
+        }
+        assert_eq!(state.grassy_neighbor_count(tile_index!(1, 1, 4)), 4);
+
+        // A grassy tile doesn't count itself.
+        state.tiles[tile_index!(1, 1, 4)].0 = GroundCover::Grass;
+        assert_eq!(state.grassy_neighbor_count(tile_index!(1, 1, 4)), 4);
+        assert_eq!(state.grassy_neighbor_count(tile_index!(3, 3, 4)), 1);
+    }
+
+    #[test]
+    fn step_keeps_going_past_u32_ticks() {
+        let mut state = bare_state(2);
+        state.tick_count = u32::MAX as u64;
+
+        state.step(Duration::from_millis(16));
+        state.step(Duration::from_millis(16));
+        assert_eq!(state.tick_count, u32::MAX as u64 + 2);
     }
 }
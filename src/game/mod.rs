@@ -1,10 +1,14 @@
 pub mod game_state;
+pub mod events;
 mod tiles;
 mod trees;
 mod position;
 mod vector;
 mod tree_region_iterator;
+mod scenario;
 
+pub use position::{TileCoordinate, TileOffset, WorldPosition};
+pub use scenario::{Scenario, ScenarioError, ScenarioPlanting, ScenarioTerrain};
 pub use tiles::{get_sprite_sheet_layout, TileType};
+pub use trees::{SeedRate, SpeciesConfig, SpeciesParams, Tree, TreeBuilder, TreeGrowthStage, TreeSpecies};
 // pub use position::RelativePosition;
-pub use trees::TreeGrowthStage;
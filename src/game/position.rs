@@ -1,6 +1,7 @@
 use super::vector::Vec2;
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TileCoordinate {
     pub x: i32,
     pub y: i32,
@@ -9,6 +10,7 @@ pub struct TileCoordinate {
 pub type TileOffset = Vec2<f32>;
 
 #[derive(Copy, Clone, Debug,)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct WorldPosition {
     pub coord: TileCoordinate,
     pub offset: TileOffset,
@@ -93,10 +95,17 @@ impl WorldPosition {
         }
     }
 
+    /// Tiles from self to other along each axis. Unlike Sub the result isn't normalized, so there's no floor to round
+    /// trip through.
+    pub fn delta(&self, other: &Self) -> (f32, f32) {
+        let diff_x = (other.coord.x - self.coord.x) as f32 + (other.offset.x - self.offset.x);
+        let diff_y = (other.coord.y - self.coord.y) as f32 + (other.offset.y - self.offset.y);
+
+        (diff_x, diff_y)
+    }
+
     pub fn distance_sq(&self, other: &Self) -> f32 {
-        let diff = (*other - *self);
-        let diff_x = diff.coord.x as f32 + diff.offset.x;
-        let diff_y = diff.coord.y as f32 + diff.offset.y;
+        let (diff_x, diff_y) = self.delta(other);
 
         (diff_x * diff_x) + (diff_y * diff_y)
     }
@@ -105,3 +114,36 @@ impl WorldPosition {
         self.distance_sq(other).sqrt()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(x: i32, y: i32, offset_x: f32, offset_y: f32) -> WorldPosition {
+        WorldPosition { coord: TileCoordinate { x, y }, offset: TileOffset { x: offset_x, y: offset_y } }
+    }
+
+    #[test]
+    fn delta_runs_across_tiles() {
+        let (dx, dy) = pos(1, 2, 0.75, 0.25).delta(&pos(3, 1, 0.25, 0.5));
+        assert!((dx - 1.5).abs() < 1e-6);
+        assert!((dy + 0.75).abs() < 1e-6);
+
+        // Several tiles apart with the offsets pulling the other way.
+        let (dx, dy) = pos(-2, 5, 0.1, 0.9).delta(&pos(4, -3, 0.8, 0.05));
+        assert!((dx - 6.7).abs() < 1e-5);
+        assert!((dy + 8.85).abs() < 1e-5);
+
+        assert_eq!(pos(1, 1, 0.5, 0.5).delta(&pos(1, 1, 0.5, 0.5)), (0.0, 0.0));
+    }
+
+    #[test]
+    fn distance_sq_is_symmetric_across_a_tile_edge() {
+        let a = pos(0, 0, 0.9, 0.5);
+        let b = pos(1, 0, 0.1, 0.5);
+
+        assert!((a.distance_sq(&b) - 0.04).abs() < 1e-6);
+        assert_eq!(a.distance_sq(&b), b.distance_sq(&a));
+        assert!((pos(0, 0, 0.0, 0.0).distance(&pos(3, 4, 0.0, 0.0)) - 5.0).abs() < 1e-6);
+    }
+}
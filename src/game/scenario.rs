@@ -0,0 +1,84 @@
+use super::game_state::{GroundCover, PlantError, SimParams, SoilType};
+use super::position::WorldPosition;
+use super::trees::{SpeciesConfig, TreeGrowthStage, TreeSpecies};
+
+/// A reproducible starting setup for sharing test cases and demos, what GameState::from_scenario builds.
+/// The hand authored counterpart to GameState::serialize.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scenario {
+    pub grid_dim: usize,
+    pub seed: u64,
+    pub terrain: ScenarioTerrain,
+    // Scatter trees at random like GameState::new_seeded does, before plantings go in.
+    pub random_trees: bool,
+    pub plantings: Vec<ScenarioPlanting>,
+    pub species_config: SpeciesConfig,
+    pub params: SimParams,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScenarioTerrain {
+    // Grass with a random walk of stony soil, like GameState::new_seeded.
+    Generated,
+    // Grass over a single soil type.
+    Uniform(SoilType),
+    // One entry per tile, row by row starting at (0, 0), grid_dim * grid_dim long.
+    Tiles(Vec<(GroundCover, SoilType)>),
+}
+
+/// A tree planted at position, already grown into stage.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScenarioPlanting {
+    pub species: TreeSpecies,
+    pub position: WorldPosition,
+    pub stage: TreeGrowthStage,
+}
+
+impl Scenario {
+    /// Same as GameState::new_seeded_with_dimensions, something to start authoring from.
+    pub fn generated(seed: u64, grid_dim: usize) -> Self {
+        Self {
+            grid_dim,
+            seed,
+            terrain: ScenarioTerrain::Generated,
+            random_trees: true,
+            plantings: Vec::new(),
+            species_config: SpeciesConfig::default(),
+            params: SimParams::default(),
+        }
+    }
+}
+
+#[cfg(feature = "scenario-json")]
+impl Scenario {
+    pub fn to_json(&self) -> String {
+        // Plain data with string keys, serializing can't fail.
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    // grid_dim is 0 or over MAX_GRID_DIM, or the tile layout doesn't have grid_dim * grid_dim entries.
+    WrongGridSize,
+    // Index into plantings of the first one that couldn't be planted.
+    Planting { index: usize, error: PlantError },
+}
+
+impl std::fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongGridSize             => write!(f, "scenario tiles don't match its grid dimensions"),
+            Self::Planting { index, error } => write!(f, "couldn't plant scenario tree {}: {}", index, error),
+        }
+    }
+}
+
+impl std::error::Error for ScenarioError { }
@@ -67,6 +67,15 @@ pub enum TileType {
     CottonWoodTreeDecline,
     CottonWoodTreeSnag,
     CottonWoodTreeStump,
+
+    OakTreeSprout,
+    OakTreeSeedling,
+    OakTreeSapling,
+    OakTreeMature,
+    OakTreeOld,
+    OakTreeDecline,
+    OakTreeSnag,
+    OakTreeStump,
 }
 
 impl Default for TileType {
@@ -84,12 +93,19 @@ impl From<TileType> for SpriteId {
 // SAFETY: TileType is an enum, fool!
 unsafe impl SpriteSetIdentifier for TileType {}
 
+// Sprites are anchored bottom center unless told otherwise.
 macro_rules! sprite {
     ($x:expr, $y:expr, $t:expr) => {
-        SpriteSheetEntry { id: $t, pos: ($x, $y) }
+        SpriteSheetEntry { id: $t, pos: ($x, $y), anchor: (0.5, 0.0) }
+    };
+    ($x:expr, $y:expr, $t:expr, $anchor:expr) => {
+        SpriteSheetEntry { id: $t, pos: ($x, $y), anchor: $anchor }
     };
 }
 
+// 1px wide trunks (Sprouts/Seedlings) sit half a pixel right of the tile center.
+const THIN_TRUNK_ANCHOR: (f32, f32) = (16.5 / 32.0, 0.0);
+
 pub fn get_sprite_sheet_layout() -> SpriteSheetLayout<TileType> {
     use TileType::*;
 
@@ -137,8 +153,8 @@ pub fn get_sprite_sheet_layout() -> SpriteSheetLayout<TileType> {
             sprite!(3, 9, StoneDiagDown),
             sprite!(4, 9, StoneDiagUp),
 
-            sprite!(0, 1, AshTreeSprout),
-            sprite!(1, 1, AshTreeSeedling),
+            sprite!(0, 1, AshTreeSprout, THIN_TRUNK_ANCHOR),
+            sprite!(1, 1, AshTreeSeedling, THIN_TRUNK_ANCHOR),
             sprite!(2, 1, AshTreeSapling),
             sprite!(3, 1, AshTreeMature),
             sprite!(4, 1, AshTreeOld),
@@ -146,8 +162,8 @@ pub fn get_sprite_sheet_layout() -> SpriteSheetLayout<TileType> {
             sprite!(6, 1, AshTreeSnag),
             sprite!(7, 1, AshTreeStump),
 
-            sprite!(0, 2, PineTreeSprout),
-            sprite!(1, 2, PineTreeSeedling),
+            sprite!(0, 2, PineTreeSprout, THIN_TRUNK_ANCHOR),
+            sprite!(1, 2, PineTreeSeedling, THIN_TRUNK_ANCHOR),
             sprite!(2, 2, PineTreeSapling),
             sprite!(3, 2, PineTreeMature),
             sprite!(4, 2, PineTreeOld),
@@ -155,14 +171,67 @@ pub fn get_sprite_sheet_layout() -> SpriteSheetLayout<TileType> {
             sprite!(6, 2, PineTreeSnag),
             sprite!(7, 2, PineTreeStump),
 
-            sprite!(0, 3, CottonWoodTreeSprout),
-            sprite!(1, 3, CottonWoodTreeSeedling),
+            sprite!(0, 3, CottonWoodTreeSprout, THIN_TRUNK_ANCHOR),
+            sprite!(1, 3, CottonWoodTreeSeedling, THIN_TRUNK_ANCHOR),
             sprite!(2, 3, CottonWoodTreeSapling),
             sprite!(3, 3, CottonWoodTreeMature),
             sprite!(4, 3, CottonWoodTreeOld),
             sprite!(5, 3, CottonWoodTreeDecline),
             sprite!(6, 3, CottonWoodTreeSnag),
             sprite!(7, 3, CottonWoodTreeStump),
+
+            // TODO: Placeholder art, row 10 is the Ash row recoloured until real Oak sprites are drawn.
+            sprite!(0, 10, OakTreeSprout, THIN_TRUNK_ANCHOR),
+            sprite!(1, 10, OakTreeSeedling, THIN_TRUNK_ANCHOR),
+            sprite!(2, 10, OakTreeSapling),
+            sprite!(3, 10, OakTreeMature),
+            sprite!(4, 10, OakTreeOld),
+            sprite!(5, 10, OakTreeDecline),
+            sprite!(6, 10, OakTreeSnag),
+            sprite!(7, 10, OakTreeStump),
         ],
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_sprite_has_its_own_cell() {
+        let layout = get_sprite_sheet_layout();
+        assert_eq!(layout.entries.len(), std::mem::variant_count::<TileType>());
+
+        for (index, entry) in layout.entries.iter().enumerate() {
+            assert!(
+                layout.entries[..index].iter().all(|other| other.pos != entry.pos && other.id != entry.id),
+                "{:?} at {:?} shares an entry", entry.id, entry.pos
+            );
+        }
+    }
+
+    #[test]
+    fn every_species_and_stage_has_its_own_registered_sprite() {
+        use crate::game::position::{TileCoordinate, TileOffset, WorldPosition};
+        use crate::game::trees::{SpeciesConfig, Tree, TreeGrowthStage, TreeSpecies};
+
+        let config = SpeciesConfig::default();
+        let layout = get_sprite_sheet_layout();
+        let position = WorldPosition { coord: TileCoordinate { x: 0, y: 0 }, offset: TileOffset { x: 0.5, y: 0.5 } };
+
+        let mut seen = Vec::new();
+        for species in TreeSpecies::ALL {
+            let mut stage = TreeGrowthStage::Sprout;
+            loop {
+                let tile_type = TileType::from(&Tree::builder(species, position).stage(stage).build(&config));
+
+                assert!(layout.entries.iter().any(|entry| entry.id == tile_type), "{:?} isn't on the sprite sheet", tile_type);
+                assert!(!seen.contains(&tile_type), "{:?} {:?} reuses {:?}", species, stage, tile_type);
+                seen.push(tile_type);
+
+                if stage == TreeGrowthStage::Stump { break; }
+                stage = stage.next();
+            }
+        }
+    }
+}
@@ -1,5 +1,5 @@
 use super::trees::Tree;
-use super::game_state::{ GRID_DIM, NUM_TREES_PER_TILE };
+use super::game_state::NUM_TREES_PER_TILE;
 use super::game_state::tree_slot_index_xyt;
 
 pub struct TreeRegionIterator<'t> {
@@ -14,13 +14,15 @@ pub struct TreeRegionIterator<'t> {
 
     tree_sub_index: usize,
 
+    grid_dim: usize,
     trees: &'t [Option<Tree>],
 }
 
 impl <'t> TreeRegionIterator<'t> {
-    pub unsafe fn new(min: (usize, usize), max: (usize, usize), trees: &'t [Option<Tree>]) -> Self {
-        debug_assert!(max.0 < GRID_DIM);
-        debug_assert!(max.1 < GRID_DIM);
+    pub unsafe fn new(min: (usize, usize), max: (usize, usize), grid_dim: usize, trees: &'t [Option<Tree>]) -> Self {
+        debug_assert!(max.0 < grid_dim);
+        debug_assert!(max.1 < grid_dim);
+        debug_assert!(trees.len() >= grid_dim * grid_dim * NUM_TREES_PER_TILE);
         debug_assert!(min.0 <= max.0);
         debug_assert!(min.1 <= max.1);
 
@@ -32,6 +34,7 @@ impl <'t> TreeRegionIterator<'t> {
             curr_x: min.0,
             curr_y: min.1,
             tree_sub_index: 0,
+            grid_dim,
             trees,
         }
     }
@@ -44,8 +47,8 @@ impl<'t> Iterator for TreeRegionIterator<'t> {
 
         while self.curr_y <= self.max_y {
             // SAFETY:
-            //  curr_x, curr_y are both in range 0..GRID_DIM, tree_sub_index is reset when >= NUM_TREES_PER_TILE
-            let slot_index = tree_slot_index_xyt!(self.curr_x, self.curr_y, self.tree_sub_index);
+            //  curr_x, curr_y are both in range 0..grid_dim, tree_sub_index is reset when >= NUM_TREES_PER_TILE
+            let slot_index = tree_slot_index_xyt!(self.curr_x, self.curr_y, self.tree_sub_index, self.grid_dim);
             let result = unsafe { self.trees.get_unchecked(slot_index) };
 
             self.tree_sub_index += 1;
@@ -82,13 +85,15 @@ pub struct TreeRegionIteratorMut<'t> {
 
     tree_sub_index: usize,
 
+    grid_dim: usize,
     trees: &'t mut [Option<Tree>],
 }
 
 impl <'t> TreeRegionIteratorMut<'t> {
-    pub unsafe fn new(min: (usize, usize), max: (usize, usize), trees: &'t mut [Option<Tree>]) -> Self {
-        debug_assert!(max.0 < GRID_DIM);
-        debug_assert!(max.1 < GRID_DIM);
+    pub unsafe fn new(min: (usize, usize), max: (usize, usize), grid_dim: usize, trees: &'t mut [Option<Tree>]) -> Self {
+        debug_assert!(max.0 < grid_dim);
+        debug_assert!(max.1 < grid_dim);
+        debug_assert!(trees.len() >= grid_dim * grid_dim * NUM_TREES_PER_TILE);
         debug_assert!(min.0 <= max.0);
         debug_assert!(min.1 <= max.1);
 
@@ -100,6 +105,7 @@ impl <'t> TreeRegionIteratorMut<'t> {
             curr_x: min.0,
             curr_y: min.1,
             tree_sub_index: 0,
+            grid_dim,
             trees,
         }
     }
@@ -112,8 +118,8 @@ impl<'t> Iterator for TreeRegionIteratorMut<'t> {
 
         while self.curr_y <= self.max_y {
             // SAFETY:
-            //  curr_x, curr_y are both in range 0..GRID_DIM, tree_sub_index is reset when >= NUM_TREES_PER_TILE
-            let slot_index = tree_slot_index_xyt!(self.curr_x, self.curr_y, self.tree_sub_index);
+            //  curr_x, curr_y are both in range 0..grid_dim, tree_sub_index is reset when >= NUM_TREES_PER_TILE
+            let slot_index = tree_slot_index_xyt!(self.curr_x, self.curr_y, self.tree_sub_index, self.grid_dim);
 
             // SAFETY:
             //  Compiler cannot statically determine that our iterator won't return a reference to the same part of the slice twice.
@@ -3,24 +3,35 @@ use super::tiles::TileType;
 use super::position::WorldPosition;
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SeedRate {
     pub average: f32,
     pub variation: f32,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TreeSpecies {
     Ash,
     Fir,
     CottonWood,
+    Oak,
 }
 
 impl TreeSpecies {
+    pub const ALL: [TreeSpecies; 4] = [
+        Self::Ash,
+        Self::Fir,
+        Self::CottonWood,
+        Self::Oak,
+    ];
+
     pub fn seed_radius(&self) -> (f32, f32) {
         match self {
             Self::Ash        => (0.4, 4.5),
             Self::Fir        => (0.3, 1.5),
             Self::CottonWood => (0.6, 6.0),
+            Self::Oak        => (0.5, 2.0),
         }
     }
 
@@ -29,6 +40,17 @@ impl TreeSpecies {
             Self::Ash        => SeedRate { average: 40.0, variation: 10.0},
             Self::Fir        => SeedRate { average: 30.0, variation: 10.0},
             Self::CottonWood => SeedRate { average: 160.0, variation: 30.0},
+            Self::Oak        => SeedRate { average: 60.0, variation: 20.0},
+        }
+    }
+
+    /// Simulated seconds a tree lives before it goes into Decline, however well it's growing.
+    pub fn max_age_s(&self) -> f32 {
+        match self {
+            Self::Ash        => 250.0,
+            Self::Fir        => 350.0,
+            Self::CottonWood => 300.0,
+            Self::Oak        => 500.0,
         }
     }
 
@@ -37,6 +59,49 @@ impl TreeSpecies {
             Self::Ash        => SoilType::Normal,
             Self::Fir        => SoilType::Stony,
             Self::CottonWood => SoilType::Normal,
+            Self::Oak        => SoilType::Normal,
+        }
+    }
+
+    /// Growth needed to leave the given stage, None for the final stage.
+    pub fn growth_required(&self, growth_stage: TreeGrowthStage) -> Option<f32> {
+        use TreeGrowthStage::*;
+        use TreeSpecies::*;
+
+        match (self, growth_stage) {
+            (Ash, Sprout)   => Some(1.0),
+            (Ash, Seedling) => Some(5.0),
+            (Ash, Sapling)  => Some(20.0),
+            (Ash, Mature)   => Some(45.0),
+            (Ash, Old)      => Some(40.0),
+            (Ash, Decline)  => Some(20.0),
+            (Ash, Snag)     => Some(10.0),
+
+            (Fir, Sprout)   => Some(5.0),
+            (Fir, Seedling) => Some(15.0),
+            (Fir, Sapling)  => Some(20.0),
+            (Fir, Mature)   => Some(60.0),
+            (Fir, Old)      => Some(60.0),
+            (Fir, Decline)  => Some(20.0),
+            (Fir, Snag)     => Some(10.0),
+
+            (CottonWood, Sprout)   => Some(2.0),
+            (CottonWood, Seedling) => Some(3.0),
+            (CottonWood, Sapling)  => Some(10.0),
+            (CottonWood, Mature)   => Some(80.0),
+            (CottonWood, Old)      => Some(75.0),
+            (CottonWood, Decline)  => Some(30.0),
+            (CottonWood, Snag)     => Some(15.0),
+
+            (Oak, Sprout)   => Some(3.0),
+            (Oak, Seedling) => Some(10.0),
+            (Oak, Sapling)  => Some(30.0),
+            (Oak, Mature)   => Some(90.0),
+            (Oak, Old)      => Some(90.0),
+            (Oak, Decline)  => Some(40.0),
+            (Oak, Snag)     => Some(20.0),
+
+            (_, Stump)    => None,
         }
     }
 
@@ -65,11 +130,105 @@ impl TreeSpecies {
                 TreeGrowthStage::Decline => 0.8,
                 _ => 0.0,
             },
+            Self::Oak => match growth_stage {
+                TreeGrowthStage::Seedling => 0.2,
+                TreeGrowthStage::Sapling => 0.6,
+                TreeGrowthStage::Mature => 1.0,
+                TreeGrowthStage::Old => 1.1,
+                TreeGrowthStage::Decline => 0.7,
+                _ => 0.0,
+            },
+        }
+    }
+}
+
+const NUM_SPECIES: usize = std::mem::variant_count::<TreeSpecies>();
+const NUM_GROWTH_STAGES: usize = std::mem::variant_count::<TreeGrowthStage>();
+
+/// Tuning for one species, the per stage arrays are indexed by TreeGrowthStage as usize.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeciesParams {
+    pub growth_required: [Option<f32>; NUM_GROWTH_STAGES],
+    // Snag and Stump should stay 0.0, dead trees are skipped when gathering shade.
+    pub shadow_radius: [f32; NUM_GROWTH_STAGES],
+    pub seed_radius: (f32, f32),
+    pub seed_success_rate: SeedRate,
+    pub max_age_s: f32,
+}
+
+impl SpeciesParams {
+    fn from_species(species: TreeSpecies) -> Self {
+        let mut growth_required = [None; NUM_GROWTH_STAGES];
+        let mut shadow_radius = [0.0; NUM_GROWTH_STAGES];
+
+        let mut stage = TreeGrowthStage::Sprout;
+        loop {
+            growth_required[stage as usize] = species.growth_required(stage);
+            shadow_radius[stage as usize] = species.shadow_radius(stage);
+
+            if stage == TreeGrowthStage::Stump { break; }
+            stage = stage.next();
+        }
+
+        Self {
+            growth_required,
+            shadow_radius,
+            seed_radius: species.seed_radius(),
+            seed_success_rate: species.seed_success_rate(),
+            max_age_s: species.max_age_s(),
+        }
+    }
+}
+
+/// Growth and shade curves for every species, what the simulation reads instead of the tables on TreeSpecies.
+/// Default copies those tables, so it behaves exactly like they do.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpeciesConfig {
+    species: [SpeciesParams; NUM_SPECIES],
+}
+
+impl Default for SpeciesConfig {
+    fn default() -> Self {
+        Self {
+            species: TreeSpecies::ALL.map(SpeciesParams::from_species),
         }
     }
 }
 
+impl SpeciesConfig {
+    pub fn get(&self, species: TreeSpecies) -> &SpeciesParams {
+        &self.species[species as usize]
+    }
+
+    pub fn get_mut(&mut self, species: TreeSpecies) -> &mut SpeciesParams {
+        &mut self.species[species as usize]
+    }
+
+    pub fn growth_required(&self, species: TreeSpecies, growth_stage: TreeGrowthStage) -> Option<f32> {
+        self.get(species).growth_required[growth_stage as usize]
+    }
+
+    pub fn shadow_radius(&self, species: TreeSpecies, growth_stage: TreeGrowthStage) -> f32 {
+        self.get(species).shadow_radius[growth_stage as usize]
+    }
+
+    pub fn seed_radius(&self, species: TreeSpecies) -> (f32, f32) {
+        self.get(species).seed_radius
+    }
+
+    pub fn seed_success_rate(&self, species: TreeSpecies) -> SeedRate {
+        self.get(species).seed_success_rate
+    }
+
+    pub fn max_age_s(&self, species: TreeSpecies) -> f32 {
+        self.get(species).max_age_s
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TreeGrowthStage {
     Sprout,
     Seedling,
@@ -127,11 +286,21 @@ impl From<&Tree> for TileType {
             (CottonWood, Decline)  => Self::CottonWoodTreeDecline,
             (CottonWood, Snag)     => Self::CottonWoodTreeSnag,
             (CottonWood, Stump)    => Self::CottonWoodTreeStump,
+
+            (Oak, Sprout)   => Self::OakTreeSprout,
+            (Oak, Seedling) => Self::OakTreeSeedling,
+            (Oak, Sapling)  => Self::OakTreeSapling,
+            (Oak, Mature)   => Self::OakTreeMature,
+            (Oak, Old)      => Self::OakTreeOld,
+            (Oak, Decline)  => Self::OakTreeDecline,
+            (Oak, Snag)     => Self::OakTreeSnag,
+            (Oak, Stump)    => Self::OakTreeStump,
         }
     }
 }
 
 #[derive(Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Tree {
     pub position: WorldPosition,
 
@@ -144,10 +313,20 @@ pub struct Tree {
 
     pub seed_timer: f32,
     pub shade_factor: f32,
+
+    // Simulated seconds since planting, counts up through Snag and Stump too.
+    pub age_s: f32,
+
+    // Drawn mirrored horizontally, purely visual.
+    pub flipped: bool,
+
+    // On fire, spreads to nearby trees and dies once burn_timer runs out.
+    pub burning: bool,
+    pub burn_timer: f32,
 }
 
 impl Tree {
-    pub fn new(species: TreeSpecies, position: WorldPosition) -> Self {
+    pub fn new(species: TreeSpecies, position: WorldPosition, config: &SpeciesConfig) -> Self {
         use TreeGrowthStage::*;
 
         let mut result = Self {
@@ -163,13 +342,30 @@ impl Tree {
             seed_timer: 1.0,
 
             shade_factor: 1.0,
+
+            age_s: 0.0,
+
+            flipped: false,
+
+            burning: false,
+            burn_timer: 0.0,
         };
 
-        result.growth_target = result.growth_required_for_next_stage();
+        result.growth_target = result.growth_required_for_next_stage(config);
         result
     }
 
-    pub fn grow(&mut self, dt_s: f32) -> TreeGrowthStage {
+    pub fn builder(species: TreeSpecies, position: WorldPosition) -> TreeBuilder {
+        TreeBuilder {
+            species,
+            position,
+            stage: TreeGrowthStage::Sprout,
+            growth: None,
+            shade_factor: 1.0,
+        }
+    }
+
+    pub fn grow(&mut self, dt_s: f32, config: &SpeciesConfig) -> TreeGrowthStage {
         if let Some(target) = self.growth_target {
             let growth_amt = self.base_growth_speed * dt_s;
             self.growth += growth_amt;
@@ -177,7 +373,7 @@ impl Tree {
             if self.growth > target {
                 self.stage = self.stage.next();
                 self.growth_target = {
-                    if let Some(new_required_growth) = self.growth_required_for_next_stage() {
+                    if let Some(new_required_growth) = self.growth_required_for_next_stage(config) {
                         Some(target + new_required_growth)
                     } else {
                         None
@@ -194,41 +390,124 @@ impl Tree {
         self.stage
     }
 
-    pub fn growth_required_for_next_stage(&self) -> Option<f32> {
-        use TreeGrowthStage::*;
-        use TreeSpecies::*;
+    /// Skips ahead to Decline, keeping growth and growth_target consistent as if it had grown there. Trees already
+    /// declining or dead are left alone.
+    pub fn age_out(&mut self, config: &SpeciesConfig) {
+        while self.stage < TreeGrowthStage::Decline {
+            // growth_target is cumulative, where this stage ends the next begins.
+            let stage_end = self.growth_target.unwrap_or(self.growth);
 
-        match (self.species, self.stage) {
-            (Ash, Sprout)   => Some(1.0),
-            (Ash, Seedling) => Some(5.0),
-            (Ash, Sapling)  => Some(20.0),
-            (Ash, Mature)   => Some(45.0),
-            (Ash, Old)      => Some(40.0),
-            (Ash, Decline)  => Some(20.0),
-            (Ash, Snag)     => Some(10.0),
-
-            (Fir, Sprout)   => Some(5.0),
-            (Fir, Seedling) => Some(15.0),
-            (Fir, Sapling)  => Some(20.0),
-            (Fir, Mature)   => Some(60.0),
-            (Fir, Old)      => Some(60.0),
-            (Fir, Decline)  => Some(20.0),
-            (Fir, Snag)     => Some(10.0),
-
-            (CottonWood, Sprout)   => Some(2.0),
-            (CottonWood, Seedling) => Some(3.0),
-            (CottonWood, Sapling)  => Some(10.0),
-            (CottonWood, Mature)   => Some(80.0),
-            (CottonWood, Old)      => Some(75.0),
-            (CottonWood, Decline)  => Some(30.0),
-            (CottonWood, Snag)     => Some(15.0),
-
-            (_, Stump)    => None,
+            self.stage = self.stage.next();
+            self.growth = stage_end;
+            self.growth_target = self.growth_required_for_next_stage(config).map(|required| stage_end + required);
         }
     }
 
+    pub fn growth_required_for_next_stage(&self, config: &SpeciesConfig) -> Option<f32> {
+        config.growth_required(self.species, self.stage)
+    }
+
     // A little counter intuitive "dead, decaying" trees still "grow", they're just unaffected by modifiers.
     pub fn is_alive(&self) -> bool {
         self.stage != TreeGrowthStage::Snag && self.stage != TreeGrowthStage::Stump
     }
 }
+
+/// Builds a Tree already at a given stage, with growth and growth_target consistent with having grown into it.
+pub struct TreeBuilder {
+    species: TreeSpecies,
+    position: WorldPosition,
+    stage: TreeGrowthStage,
+    growth: Option<f32>,
+    shade_factor: f32,
+}
+
+impl TreeBuilder {
+    pub fn stage(mut self, stage: TreeGrowthStage) -> Self {
+        self.stage = stage;
+        self
+    }
+
+    /// Total accumulated growth, defaults to the amount needed to have just reached the stage.
+    pub fn growth(mut self, growth: f32) -> Self {
+        self.growth = Some(growth);
+        self
+    }
+
+    pub fn shade_factor(mut self, shade_factor: f32) -> Self {
+        self.shade_factor = shade_factor;
+        self
+    }
+
+    pub fn build(self, config: &SpeciesConfig) -> Tree {
+        let mut result = Tree::new(self.species, self.position, config);
+
+        // Walk up through the stages, growth_target is cumulative so we need to know where this stage began.
+        let mut stage_begin = 0.0;
+        while result.stage != self.stage {
+            stage_begin += result.growth_required_for_next_stage(config).unwrap_or(0.0);
+            result.stage = result.stage.next();
+        }
+
+        result.growth = self.growth.unwrap_or(stage_begin);
+        result.growth_target = result.growth_required_for_next_stage(config).map(|required| stage_begin + required);
+        result.shade_factor = self.shade_factor;
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::position::{TileCoordinate, TileOffset};
+
+    fn position() -> WorldPosition {
+        WorldPosition { coord: TileCoordinate { x: 1, y: 1 }, offset: TileOffset { x: 0.5, y: 0.5 } }
+    }
+
+    #[test]
+    fn built_trees_match_ones_grown_into_their_stage() {
+        let config = SpeciesConfig::default();
+        let built = Tree::builder(TreeSpecies::CottonWood, position())
+            .stage(TreeGrowthStage::Mature)
+            .shade_factor(0.6)
+            .build(&config);
+
+        // Sprout 2 + Seedling 3 + Sapling 10 to get here, Mature needs another 80.
+        assert_eq!(built.stage, TreeGrowthStage::Mature);
+        assert_eq!(built.growth, 15.0);
+        assert_eq!(built.growth_target, Some(95.0));
+        assert_eq!(built.shade_factor, 0.6);
+
+        let mut grown = Tree::new(TreeSpecies::CottonWood, position(), &config);
+        while grown.stage != TreeGrowthStage::Mature {
+            grown.grow(0.25, &config);
+        }
+        assert_eq!(grown.growth_target, built.growth_target);
+    }
+
+    #[test]
+    fn default_config_matches_the_species_tables() {
+        let config = SpeciesConfig::default();
+
+        for species in TreeSpecies::ALL {
+            let mut stage = TreeGrowthStage::Sprout;
+            loop {
+                assert_eq!(config.growth_required(species, stage), species.growth_required(stage), "{:?} {:?}", species, stage);
+                assert_eq!(config.shadow_radius(species, stage), species.shadow_radius(stage), "{:?} {:?}", species, stage);
+
+                if stage == TreeGrowthStage::Stump { break; }
+                stage = stage.next();
+            }
+
+            assert_eq!(config.seed_radius(species), species.seed_radius());
+            assert_eq!(config.max_age_s(species), species.max_age_s());
+        }
+
+        // Spot check against the numbers the simulation was tuned with.
+        assert_eq!(config.growth_required(TreeSpecies::Ash, TreeGrowthStage::Mature), Some(45.0));
+        assert_eq!(config.shadow_radius(TreeSpecies::Fir, TreeGrowthStage::Mature), 0.7);
+        assert_eq!(config.seed_success_rate(TreeSpecies::CottonWood).average, 160.0);
+    }
+}
@@ -41,6 +41,26 @@ macro_rules! impl_v2 {
             pub fn clamp01(self) -> Self {
                 self.clamp(0.0, 1.0)
             }
+
+            /// Unit vector at angle radians counter clockwise from +x.
+            pub fn from_angle(angle: $s) -> Self {
+                let (sin, cos) = angle.sin_cos();
+                Self { x: cos, y: sin }
+            }
+
+            /// Radians counter clockwise from +x, in -PI..=PI.
+            pub fn angle(self) -> $s {
+                self.y.atan2(self.x)
+            }
+
+            /// Counter clockwise by angle radians.
+            pub fn rotate(self, angle: $s) -> Self {
+                let (sin, cos) = angle.sin_cos();
+                Self {
+                    x: (self.x * cos) - (self.y * sin),
+                    y: (self.x * sin) + (self.y * cos),
+                }
+            }
         }
 
         //
@@ -472,6 +492,7 @@ macro_rules! impl_v4 {
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vec2<T>
 {
     pub x: T,
@@ -503,3 +524,30 @@ pub struct Vec4<T> {
 
 impl_v4!(f32);
 impl_v4!(f64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::f32::consts::FRAC_PI_2;
+
+    #[test]
+    fn rotating_x_a_quarter_turn_gives_y() {
+        let rotated = Vec2::<f32>::new(1.0, 0.0).rotate(FRAC_PI_2);
+        assert!(rotated.x.abs() < 1e-6);
+        assert!((rotated.y - 1.0).abs() < 1e-6);
+
+        // Rotation keeps length.
+        let v = Vec2::<f32>::new(3.0, -4.0);
+        assert!((v.rotate(1.3).length() - v.length()).abs() < 1e-5);
+    }
+
+    #[test]
+    fn from_angle_round_trips_through_angle() {
+        for angle in [0.0, 0.5, FRAC_PI_2, 2.0, 3.0, -0.5, -FRAC_PI_2, -3.0] {
+            let v = Vec2::<f32>::from_angle(angle);
+            assert!((v.length() - 1.0).abs() < 1e-6);
+            assert!((v.angle() - angle).abs() < 1e-6, "{} came back as {}", angle, v.angle());
+        }
+    }
+}
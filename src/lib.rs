@@ -0,0 +1,10 @@
+#![feature(generic_const_exprs)]
+#![feature(slice_index_methods)]
+#![feature(variant_count)]
+#![allow(unused_parens)]
+
+// The simulation doesn't need a window or a gpu, tests and benchmarks can drive game::game_state::GameState directly.
+pub mod debug;
+pub mod game;
+pub mod render;
+pub mod timer;
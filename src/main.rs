@@ -1,6 +1,3 @@
-#![feature(generic_const_exprs)]
-#![feature(slice_index_methods)]
-#![feature(variant_count)]
 #![allow(unused_parens)]
 
 use std::fmt::Debug;
@@ -11,21 +8,81 @@ use winit::event::*;
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::WindowBuilder;
 
-mod debug;
-mod game;
-mod render;
-mod timer;
+mod bug_report;
+mod config;
 
-use debug::DebugTimers;
-use game::game_state::{GameState, Input};
-use render::RenderState;
-use timer::{AverageDurationTimer, DurationTimer, TargetTimer, Timer, TimerState};
+use config::{SimConfig, UPS_NUDGE};
+use tree_sprites::debug::DebugTimers;
+use tree_sprites::game::TreeSpecies;
+use tree_sprites::game::game_state::{GameState, Input};
+use tree_sprites::render::{RenderState, ShaderId};
+use tree_sprites::timer::{measure, AverageDurationTimer, DurationTimer, PeakTracker, TargetTimer, Timer, TimerState};
 
-fn process_window_event(event: WindowEvent, render_state: &mut RenderState, input_state: &mut Input, control_flow: &mut ControlFlow) {
+// For profiling, separate from the in-sim pause: turn off one half of the loop to see what the other costs alone.
+struct LoopToggles {
+    sim_enabled: bool,
+    render_enabled: bool,
+}
+
+impl Default for LoopToggles {
+    fn default() -> Self {
+        Self { sim_enabled: true, render_enabled: true }
+    }
+}
+
+impl LoopToggles {
+    /// Adds elapsed to the accumulator and drains it one fixed step per update owed. Sim time stands still while
+    /// disabled, rather than catching up all at once when re-enabled.
+    fn take_updates(&self, accumulator: &mut Duration, elapsed: Duration, step: Duration) -> u32 {
+        if self.sim_enabled {
+            *accumulator += elapsed;
+        }
+
+        let mut count = 0;
+        while *accumulator > step {
+            count += 1;
+            *accumulator -= step;
+        }
+
+        count
+    }
+}
+
+fn process_window_event(
+    event: WindowEvent,
+    render_state: &mut RenderState,
+    input_state: &mut Input,
+    toggles: &mut LoopToggles,
+    config: &mut SimConfig,
+    control_flow: &mut ControlFlow
+) {
     match event {
         WindowEvent::CloseRequested                            => *control_flow = ControlFlow::Exit,
         WindowEvent::Resized(physical_size)                    => render_state.resize(physical_size),
         WindowEvent::ScaleFactorChanged { new_inner_size, .. } => render_state.resize(*new_inner_size),
+        WindowEvent::CursorMoved { position, .. } => {
+            input_state.cursor_position = Some(position);
+            render_state.set_cursor_position(Some(position));
+        },
+        WindowEvent::CursorLeft { .. } => {
+            input_state.cursor_position = None;
+            render_state.set_cursor_position(None);
+        },
+        WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Left, .. } => {
+            if let Some(position) = input_state.cursor_position {
+                input_state.left_click = Some(render_state.screen_to_world(position));
+            }
+        },
+        WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Right, .. } => {
+            if let Some(position) = input_state.cursor_position {
+                input_state.right_click = Some(render_state.screen_to_world(position));
+            }
+        },
+        WindowEvent::MouseInput { state: ElementState::Pressed, button: MouseButton::Middle, .. } => {
+            if let Some(position) = input_state.cursor_position {
+                input_state.middle_click = Some(render_state.screen_to_world(position));
+            }
+        },
 
         WindowEvent::KeyboardInput { input, .. } => {
 
@@ -56,6 +113,49 @@ fn process_window_event(event: WindowEvent, render_state: &mut RenderState, inpu
                         VirtualKeyCode::G => input_state.show_grid = !input_state.show_grid,
                         VirtualKeyCode::H => input_state.show_dual = !input_state.show_dual,
                         VirtualKeyCode::T => input_state.show_trees = !input_state.show_trees,
+                        VirtualKeyCode::B => input_state.hide_apron = !input_state.hide_apron,
+                        VirtualKeyCode::V => input_state.show_debug_vectors = !input_state.show_debug_vectors,
+                        VirtualKeyCode::M => input_state.slow_motion_on_die_off = !input_state.slow_motion_on_die_off,
+                        VirtualKeyCode::I => input_state.show_slot_indices = !input_state.show_slot_indices,
+                        VirtualKeyCode::O => input_state.show_seed_dispersal = !input_state.show_seed_dispersal,
+                        VirtualKeyCode::X => input_state.show_origin = !input_state.show_origin,
+
+                        VirtualKeyCode::LBracket => {
+                            input_state.slow_down();
+                            debug!("Sim speed: {}", input_state.sim_speed);
+                        },
+                        VirtualKeyCode::RBracket => {
+                            input_state.speed_up();
+                            debug!("Sim speed: {}", input_state.sim_speed);
+                        },
+
+                        VirtualKeyCode::Key1 => input_state.selected_species = TreeSpecies::Ash,
+                        VirtualKeyCode::Key2 => input_state.selected_species = TreeSpecies::Fir,
+                        VirtualKeyCode::Key3 => input_state.selected_species = TreeSpecies::CottonWood,
+                        VirtualKeyCode::Key4 => input_state.selected_species = TreeSpecies::Oak,
+
+                        VirtualKeyCode::F5 => reload_shaders(render_state),
+                        VirtualKeyCode::F6 => {
+                            toggles.sim_enabled = !toggles.sim_enabled;
+                            debug!("Sim enabled: {}", toggles.sim_enabled);
+                        },
+                        VirtualKeyCode::F7 => {
+                            toggles.render_enabled = !toggles.render_enabled;
+                            debug!("Render enabled: {}", toggles.render_enabled);
+                        },
+                        VirtualKeyCode::PageUp | VirtualKeyCode::PageDown => {
+                            let delta = if keycode == VirtualKeyCode::PageUp { UPS_NUDGE } else { -UPS_NUDGE };
+                            config.nudge_ups_target(delta);
+                            debug!("Targeting {} UPS", config.ups_target);
+                        },
+                        VirtualKeyCode::F8 => {
+                            let present_mode = match render_state.present_mode() {
+                                wgpu::PresentMode::Fifo => wgpu::PresentMode::Mailbox,
+                                _ => wgpu::PresentMode::Fifo,
+                            };
+                            render_state.set_present_mode(present_mode);
+                        },
+                        VirtualKeyCode::F12 => render_state.request_capture(),
 
                         VirtualKeyCode::LControl => input_state.zoom_in  = false,
                         VirtualKeyCode::Space    => input_state.zoom_out = false,
@@ -71,6 +171,28 @@ fn process_window_event(event: WindowEvent, render_state: &mut RenderState, inpu
     }
 }
 
+// Picks up edits to the .wgsl files without restarting, run from the crate root.
+fn reload_shaders(render_state: &mut RenderState) {
+    for shader in ShaderId::ALL {
+        let result = std::fs::read_to_string(shader.path())
+            .map_err(anyhow::Error::from)
+            .and_then(|src| render_state.reload_shader(shader, &src));
+
+        match result {
+            Ok(_)  => debug!("Reloaded {}", shader.path()),
+            Err(e) => error!("Failed to reload {}: {:?}", shader.path(), e),
+        }
+    }
+}
+
+// Where a frame's time went, logged for the worst frame of each window since spikes vanish in the averages.
+#[derive(Clone, Copy, Debug)]
+struct FrameBreakdown {
+    updates: u32,
+    update_time: Duration,
+    render_time: Duration,
+}
+
 type RenderResult = Result<(), wgpu::SurfaceError>;
 
 fn handle_render_result(render_result: RenderResult, render_state: &mut RenderState, window: &winit::window::Window, control_flow: &mut ControlFlow) {
@@ -98,24 +220,24 @@ fn handle_render_result(render_result: RenderResult, render_state: &mut RenderSt
     }
 }
 
-const UPS_TARGET: u64 = 120;
-const FPS_TARGET: u64 = 120;
-
 fn main() {
-    //Duration constructor is unstable as constfn;
-    let update_target_dt = Duration::from_secs_f32(1.0 / (UPS_TARGET as f32));
-    let frame_target_dt = Duration::from_secs_f32(1.0 / (FPS_TARGET as f32));
-
     env_logger::init();
     debug!("Logger initialized");
 
+    let mut config = SimConfig::from_env();
+    let update_target_dt = config.update_target_dt();
+    let frame_target_dt = config.frame_target_dt();
+    debug!("Targeting {} UPS, {} FPS", config.ups_target, config.fps_target);
+
     let event_loop = EventLoop::new();
     let window = WindowBuilder::new().build(&event_loop).unwrap();
 
     let mut game_state = Box::new(GameState::new());
-    let mut render_state = pollster::block_on(RenderState::new(&window, &game_state));
+    let mut render_state = pollster::block_on(RenderState::new(&window, &game_state, frame_target_dt));
+    debug!("Adapter: {:?}, surface format: {:?}", render_state.adapter_info(), render_state.surface_format());
 
     let mut input = Input::default();
+    let mut toggles = LoopToggles::default();
 
     let mut dbgt = DebugTimers::new();
 
@@ -125,17 +247,24 @@ fn main() {
     let mut window_title_update_timer = TargetTimer::new(Duration::from_secs_f32(0.25));
 
     let mut loop_timer = DurationTimer::new();
+    let mut section_timer = DurationTimer::new();
+    let mut worst_frame = PeakTracker::<FrameBreakdown>::new();
     let mut sim_time = Duration::from_secs(0);
+    // Every update's dt summed, has to track sim_time exactly or the sim drifts from the clock it's drawn against.
+    let mut sim_dt_total = Duration::from_secs(0);
     let mut accumulator = Duration::from_secs(0);
 
     event_loop.run(move |event, _, control_flow| {
         match event {
             Event::WindowEvent { window_id, event } => {
                 if window_id == window.id() {
-                    process_window_event(event, &mut render_state, &mut input, control_flow);
+                    process_window_event(event, &mut render_state, &mut input, &mut toggles, &mut config, control_flow);
                 }
             },
             Event::MainEventsCleared => {
+                // The UPS target can be nudged at runtime.
+                let update_target_dt = config.update_target_dt();
+
                 let mut elapsed = update_timer.elapsed();
                 update_timer.reset();
 
@@ -143,18 +272,15 @@ fn main() {
                     elapsed = update_target_dt;
                 }
 
-                accumulator += elapsed;
-
-                let mut count = 0;
-
-                while accumulator > update_target_dt {
-                    count += 1;
-
-                    sim_time    += update_target_dt;
-                    accumulator -= update_target_dt;
+                section_timer.reset();
+                let count = toggles.take_updates(&mut accumulator, elapsed, update_target_dt);
+                for _ in 0..count {
+                    sim_time += update_target_dt;
 
-                    input.dt = update_target_dt * 2;
+                    // One update consumes exactly one step of the accumulator.
+                    input.dt = update_target_dt;
                     input.t = sim_time;
+                    sim_dt_total += input.dt;
 
                     measure!(dbgt.long_avg_update_timer, {
                         measure!(dbgt.avg_update_timer, {
@@ -163,20 +289,54 @@ fn main() {
                     });
                 }
 
+                let update_time = section_timer.elapsed();
+                debug_assert_eq!(sim_dt_total, sim_time);
+
+                // Clicks are handled once, by the first update to see them.
+                if count > 0 {
+                    input.left_click = None;
+                    input.right_click = None;
+                    input.middle_click = None;
+                }
+
                 if count > 1 {
                     debug!("+{} updates...", count);
                 }
 
-                // NOTE: Timing happens internally
-                let render_result = render_state.try_render(&game_state, &mut dbgt);
-                handle_render_result(render_result, &mut render_state, &window, control_flow);
+                section_timer.reset();
+                if toggles.render_enabled {
+                    // NOTE: Timing happens internally
+                    let render_result = render_state.try_render(&game_state, sim_time, &mut dbgt);
+                    handle_render_result(render_result, &mut render_state, &window, control_flow);
+
+                    if let Some(frame) = render_state.take_captured_frame() {
+                        match bug_report::write(&config.capture_dir, &frame, &game_state, &render_state) {
+                            Ok(path) => debug!("Bug report written to {}", path.display()),
+                            Err(e)   => error!("Failed to write bug report: {:?}", e),
+                        }
+                    }
+                }
+
+                let render_time = section_timer.elapsed();
 
                 let loop_time = loop_timer.elapsed();
                 loop_timer.reset();
 
+                worst_frame.record(loop_time, FrameBreakdown { updates: count, update_time, render_time });
+
                 if let TimerState::Ready(_) = window_title_update_timer.check() {
                     window_title_update_timer.reset();
 
+                    if let Some((frame_time, breakdown)) = worst_frame.take() {
+                        debug!(
+                            "Worst frame {:.02}ms: {} updates {:.02}ms, render {:.02}ms",
+                            frame_time.as_secs_f32() * 1000.0,
+                            breakdown.updates,
+                            breakdown.update_time.as_secs_f32() * 1000.0,
+                            breakdown.render_time.as_secs_f32() * 1000.0,
+                        );
+                    }
+
                     let rps = 1.0 / loop_time.as_secs_f32();
 
                     let avg_ut = dbgt.avg_update_timer.average().as_micros();
@@ -187,11 +347,14 @@ fn main() {
                     let fps_budget_usage   = (avg_rt as f32 / frame_target_dt.as_micros() as f32) * 100.0;
                     let total_budget_usage = (avg_total as f32 / frame_target_dt.as_micros() as f32) * 100.0;
 
+                    let adapter_info = render_state.adapter_info();
+
                     window.set_title(&format!(
-                        "RPS {:.0} ({:.02}μs : {:.02}%) - UPS ({:.02}μs : {:.02}%) --- Total: {:.02}μs : {:.02}%",
+                        "RPS {:.0} ({:.02}μs : {:.02}%) - UPS ({:.02}μs : {:.02}%) --- Total: {:.02}μs : {:.02}% --- {} ({:?}, {:?})",
                         rps, avg_rt, fps_budget_usage,
                         avg_ut, ups_budget_usage,
-                        avg_total, total_budget_usage
+                        avg_total, total_budget_usage,
+                        adapter_info.name, adapter_info.backend, render_state.present_mode()
                     ));
                 }
             }
@@ -199,3 +362,60 @@ fn main() {
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabling_the_sim_skips_updates_but_not_rendering() {
+        let step = Duration::from_millis(10);
+        let mut accumulator = Duration::ZERO;
+
+        let toggles = LoopToggles { sim_enabled: false, ..LoopToggles::default() };
+        assert_eq!(toggles.take_updates(&mut accumulator, Duration::from_millis(35), step), 0);
+        assert_eq!(accumulator, Duration::ZERO);
+        assert!(toggles.render_enabled);
+
+        // Re-enabled, only time from then on is owed.
+        let toggles = LoopToggles::default();
+        assert_eq!(toggles.take_updates(&mut accumulator, Duration::from_millis(35), step), 3);
+        assert_eq!(accumulator, Duration::from_millis(5));
+    }
+
+    #[test]
+    fn nudging_the_ups_target_changes_the_step_the_loop_takes() {
+        let mut config = SimConfig { ups_target: 100, ..SimConfig::default() };
+        let toggles = LoopToggles::default();
+        let elapsed = Duration::from_millis(207);
+
+        let mut accumulator = Duration::ZERO;
+        assert_eq!(toggles.take_updates(&mut accumulator, elapsed, config.update_target_dt()), 20);
+
+        config.nudge_ups_target(UPS_NUDGE * 10);
+        let mut accumulator = Duration::ZERO;
+        assert_eq!(toggles.take_updates(&mut accumulator, elapsed, config.update_target_dt()), 41);
+    }
+
+    #[test]
+    fn updates_consume_exactly_the_sim_time_that_elapsed() {
+        let step = Duration::from_micros(8333);
+        let toggles = LoopToggles::default();
+        let mut accumulator = Duration::ZERO;
+        let mut sim_time = Duration::ZERO;
+        let mut wall_time = Duration::ZERO;
+
+        for frame_ms in [16, 7, 33, 1, 16, 250, 16] {
+            let elapsed = Duration::from_millis(frame_ms);
+            wall_time += elapsed;
+
+            // Each update is fed dt = step, the same amount it takes off the accumulator.
+            for _ in 0..toggles.take_updates(&mut accumulator, elapsed, step) {
+                sim_time += step;
+            }
+        }
+
+        assert_eq!(sim_time + accumulator, wall_time);
+        assert!(accumulator <= step);
+    }
+}
@@ -1,4 +1,5 @@
 use std::mem::size_of;
+use std::ops::{Deref, DerefMut, Range};
 use std::slice::SliceIndex;
 
 use super::super::buffer_usages::BufferUsages;
@@ -6,17 +7,41 @@ use super::super::utils::gpu::create_buffer;
 use super::super::vertex::Vertex;
 use super::{Buffer, Index, WriteGeometryBuffer, DrawGeometryBuffer};
 
+/// What push_geometry does when the buffer is full.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GrowthPolicy {
+    // Drop the geometry and flag the buffer as over capacity.
+    Fixed,
+    // Double capacity until it fits, the gpu buffers are recreated by the next reallocate_if_grown.
+    Grow,
+}
+
 pub struct GeometryBuffer<V: Vertex, I: Index> {
+    label: &'static str,
+    // Derefs to this, pushes and edits land here and are uploaded by write_geometry_buffer.
+    cache: GeometryCache<V, I>,
+
+    pub vertex_buffer: wgpu::Buffer,
+    pub index_buffer: wgpu::Buffer,
+}
+
+/// Everything GeometryBuffer keeps cpu side, and what its gpu buffers still need uploading from it.
+pub struct GeometryCache<V: Vertex, I: Index> {
+    growth_policy: GrowthPolicy,
+
     pending_writes: bool,
     over_capacity: bool,
+    // Capacity has grown past the size of the gpu buffers.
+    gpu_buffers_stale: bool,
+    // Parts of the caches modified since the last write_geometry_buffer, only these are uploaded.
+    dirty_vertices: Option<Range<usize>>,
+    dirty_indices: Option<Range<usize>>,
 
-    pub vertex_capacity: usize,
-    vertex_cache: Vec<V>,
-    pub vertex_buffer: wgpu::Buffer,
+    vertex_capacity: usize,
+    vertices: Vec<V>,
 
-    pub index_capacity: usize,
-    index_cache: Vec<I>,
-    pub index_buffer: wgpu::Buffer,
+    index_capacity: usize,
+    indices: Vec<I>,
 }
 
 impl<V: Vertex, I: Index> GeometryBuffer<V, I> {
@@ -24,27 +49,24 @@ impl<V: Vertex, I: Index> GeometryBuffer<V, I> {
         debug_assert!(vertex_capacity > 0);
         debug_assert!(index_capacity > 0);
 
-        let vertex_buffer = create_buffer(device, &format!("{}.vertex_buffer", label), vertex_capacity * size_of::<V>(), BufferUsages::VertexCopyDst.into());
-        let index_buffer  = create_buffer(device, &format!("{}.index_buffer", label),  index_capacity * size_of::<I>(), BufferUsages::IndexCopyDst.into());
-
-        let vertex_cache = Vec::with_capacity(vertex_capacity);
-        let index_cache = Vec::with_capacity(index_capacity);
+        let (vertex_buffer, index_buffer) = Self::create_gpu_buffers(device, label, vertex_capacity, index_capacity);
 
         Self {
-            pending_writes: false,
-            over_capacity: false,
+            label,
+            cache: GeometryCache::new(vertex_capacity, index_capacity),
 
-            vertex_capacity,
-            index_capacity,
-
-            vertex_cache,
             vertex_buffer,
-
-            index_cache,
             index_buffer,
         }
     }
 
+    fn create_gpu_buffers(device: &wgpu::Device, label: &str, vertex_capacity: usize, index_capacity: usize) -> (wgpu::Buffer, wgpu::Buffer) {
+        let vertex_buffer = create_buffer(device, &format!("{}.vertex_buffer", label), vertex_capacity * size_of::<V>(), BufferUsages::VertexCopyDst.into());
+        let index_buffer  = create_buffer(device, &format!("{}.index_buffer", label),  index_capacity * size_of::<I>(), BufferUsages::IndexCopyDst.into());
+
+        (vertex_buffer, index_buffer)
+    }
+
     pub fn new_with_quad_capacity(device: &wgpu::Device, label: &'static str, quad_capacity: usize) -> Self {
         let vertex_capacity = quad_capacity * 4;
         let index_capacity = quad_capacity * 6;
@@ -52,6 +74,59 @@ impl<V: Vertex, I: Index> GeometryBuffer<V, I> {
         Self::new(device, label, vertex_capacity, index_capacity)
     }
 
+    /// Recreates the gpu buffers if capacity grew, must happen before the next write_geometry_buffer.
+    pub fn reallocate_if_grown(&mut self, device: &wgpu::Device) {
+        if !self.cache.gpu_buffers_stale {
+            return;
+        }
+
+        log::debug!("Growing {} to {} vertices, {} indices.", self.label, self.cache.vertex_capacity, self.cache.index_capacity);
+
+        // Everything in the caches is re-uploaded below, so there's nothing to copy from the old buffers.
+        let (vertex_buffer, index_buffer) = Self::create_gpu_buffers(device, self.label, self.cache.vertex_capacity, self.cache.index_capacity);
+        self.vertex_buffer = vertex_buffer;
+        self.index_buffer = index_buffer;
+        self.cache.gpu_buffers_stale = false;
+
+        // Fresh buffers are empty.
+        self.cache.dirty_vertices = Some(0..self.vertex_count());
+        self.cache.dirty_indices = Some(0..self.index_count());
+    }
+}
+
+impl<V: Vertex, I: Index> Deref for GeometryBuffer<V, I> {
+    type Target = GeometryCache<V, I>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.cache
+    }
+}
+
+impl<V: Vertex, I: Index> DerefMut for GeometryBuffer<V, I> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.cache
+    }
+}
+
+impl<V: Vertex, I: Index> GeometryCache<V, I> {
+    fn new(vertex_capacity: usize, index_capacity: usize) -> Self {
+        Self {
+            growth_policy: GrowthPolicy::Grow,
+
+            pending_writes: false,
+            over_capacity: false,
+            gpu_buffers_stale: false,
+            dirty_vertices: None,
+            dirty_indices: None,
+
+            vertex_capacity,
+            vertices: Vec::with_capacity(vertex_capacity),
+
+            index_capacity,
+            indices: Vec::with_capacity(index_capacity),
+        }
+    }
+
     pub fn has_capacity(&self, new_vertices: &[V], new_indices: &[I]) -> bool {
         let has_vert_cap = new_vertices.len() <= self.remaining_vertex_capacity();
         let has_index_cap = new_indices.len() <= self.remaining_index_capacity();
@@ -59,11 +134,52 @@ impl<V: Vertex, I: Index> GeometryBuffer<V, I> {
         (has_vert_cap && has_index_cap)
     }
 
+    pub fn set_growth_policy(&mut self, growth_policy: GrowthPolicy) {
+        self.growth_policy = growth_policy;
+    }
+
+    /// Doubles capacity until the new geometry fits, false if the policy is Fixed or the indices couldn't address it.
+    /// The gpu buffers are recreated by the next reallocate_if_grown.
+    fn try_grow(&mut self, new_vertex_count: usize, new_index_count: usize) -> bool {
+        if self.growth_policy == GrowthPolicy::Fixed {
+            return false;
+        }
+
+        let required_vertices = self.vertices.len() + new_vertex_count;
+        let required_indices = self.indices.len() + new_index_count;
+
+        // Past this the indices can't address the vertices.
+        let max_vertices = I::MAX + 1;
+        let vertex_capacity = match grown_capacity(self.vertex_capacity, required_vertices, max_vertices) {
+            Some(capacity) => capacity,
+            None => return false,
+        };
+        let index_capacity = grown_capacity(self.index_capacity, required_indices, usize::MAX).unwrap();
+
+        if vertex_capacity != self.vertex_capacity {
+            self.vertex_capacity = vertex_capacity;
+            self.vertices.reserve(self.vertex_capacity - self.vertices.len());
+            self.gpu_buffers_stale = true;
+        }
+
+        if index_capacity != self.index_capacity {
+            self.index_capacity = index_capacity;
+            self.indices.reserve(self.index_capacity - self.indices.len());
+            self.gpu_buffers_stale = true;
+        }
+
+        true
+    }
+
     pub fn push_geometry(&mut self, new_vertices: &[V], new_indices: &[I]) {
         self.pending_writes = true;
-        if self.has_capacity(new_vertices, new_indices) {
-            self.vertex_cache.extend(new_vertices);
-            self.index_cache.extend(new_indices);
+        if self.has_capacity(new_vertices, new_indices) || self.try_grow(new_vertices.len(), new_indices.len()) {
+            let (v_base, i_base) = (self.vertices.len(), self.indices.len());
+            extend_dirty_range(&mut self.dirty_vertices, v_base..(v_base + new_vertices.len()));
+            extend_dirty_range(&mut self.dirty_indices, i_base..(i_base + new_indices.len()));
+
+            self.vertices.extend(new_vertices);
+            self.indices.extend(new_indices);
         } else {
             self.over_capacity = true;
         }
@@ -74,58 +190,171 @@ impl<V: Vertex, I: Index> GeometryBuffer<V, I> {
         Q: Into<[V; 4]>
     {
         let new_vertices: &[V; 4] = &quad.into();
-        let new_indices = self.generate_quad_vertices();
+        let new_indices = quad_indices(self.vertex_count());
 
         self.push_geometry(new_vertices, &new_indices);
     }
 
-    // pub fn push_quad_vertices<Q>(&mut self, new_vertices: &[V; 4]) {
-    //     let new_indices = self.generate_quad_vertices();
+    /// For buffers drawn with a LineList pipeline.
+    pub fn push_line(&mut self, from: V, to: V) {
+        let new_indices = line_indices(self.vertex_count());
 
-    //     self.push_geometry(new_vertices, &new_indices);
-    // }
+        self.push_geometry(&[from, to], &new_indices);
+    }
+
+    /// Appends other's cached geometry, rebasing its indices onto the vertices already in self.
+    pub fn merge(&mut self, other: &GeometryCache<V, I>) -> anyhow::Result<()> {
+        let v_base = self.vertices.len();
 
-    fn generate_quad_vertices(&self) -> [I; 6] {
-        let v_base = self.vertex_cache.len();
+        let max_index = other.indices.iter().map(|i| i.as_usize()).max();
+        if max_index.is_some_and(|max_index| v_base + max_index > I::MAX) {
+            anyhow::bail!("Merging would rebase indices past {}, the largest the index type can hold.", I::MAX);
+        }
+
+        if !self.has_capacity(&other.vertices, &other.indices) && !self.try_grow(other.vertices.len(), other.indices.len()) {
+            anyhow::bail!(
+                "Merging {} vertices, {} indices would exceed capacity ({} vertices, {} indices remaining).",
+                other.vertices.len(), other.indices.len(),
+                self.remaining_vertex_capacity(), self.remaining_index_capacity()
+            );
+        }
 
-        [
-            I::from_usize(v_base + 0), I::from_usize(v_base + 1), I::from_usize(v_base + 2),
-            I::from_usize(v_base + 2), I::from_usize(v_base + 3), I::from_usize(v_base + 0)
-        ]
+        let i_base = self.indices.len();
+        extend_dirty_range(&mut self.dirty_vertices, v_base..(v_base + other.vertices.len()));
+        extend_dirty_range(&mut self.dirty_indices, i_base..(i_base + other.indices.len()));
+
+        self.pending_writes = true;
+        self.vertices.extend(&other.vertices);
+        self.indices.extend(other.indices.iter().map(|i| I::from_usize(v_base + i.as_usize())));
+
+        Ok(())
     }
 
+    // pub fn push_quad_vertices<Q>(&mut self, new_vertices: &[V; 4]) {
+    //     let new_indices = quad_indices(self.vertices.len());
+
+    //     self.push_geometry(new_vertices, &new_indices);
+    // }
+
     pub fn get<S>(&self, index: S) -> Option<&<S as SliceIndex<[V]>>::Output>
     where
         S: SliceIndex<[V]>
     {
-        self.vertex_cache.get(index)
+        self.vertices.get(index)
     }
 
+    /// The four vertices of the quad_index'th quad pushed, only they are marked for upload. For buffers of quads only.
+    pub fn quad_vertices_mut(&mut self, quad_index: usize) -> Option<&mut [V]> {
+        let range = (quad_index * 4)..((quad_index + 1) * 4);
+        if range.end > self.vertices.len() { return None; }
+
+        self.pending_writes = true;
+        extend_dirty_range(&mut self.dirty_vertices, range.clone());
+
+        self.vertices.get_mut(range)
+    }
+
+    /// Marks every vertex for upload since there's no telling which ones S covers, prefer quad_vertices_mut.
     pub fn get_mut<S>(&mut self, index: S) -> Option<&mut <S as SliceIndex<[V]>>::Output>
     where
         S: SliceIndex<[V]>
     {
-        self.vertex_cache.get_mut(index)
+        self.pending_writes = true;
+        extend_dirty_range(&mut self.dirty_vertices, 0..self.vertices.len());
+
+        self.vertices.get_mut(index)
     }
 }
 
+/// Two triangles over the four vertices pushed after v_base.
+fn quad_indices<I: Index>(v_base: usize) -> [I; 6] {
+    [
+        I::from_usize(v_base + 0), I::from_usize(v_base + 1), I::from_usize(v_base + 2),
+        I::from_usize(v_base + 2), I::from_usize(v_base + 3), I::from_usize(v_base + 0)
+    ]
+}
+
+fn line_indices<I: Index>(v_base: usize) -> [I; 2] {
+    [I::from_usize(v_base), I::from_usize(v_base + 1)]
+}
+
+/// Capacity once grown to fit required, the next power of two capped at max. None if required is over max.
+fn grown_capacity(capacity: usize, required: usize, max: usize) -> Option<usize> {
+    if required > max { return None; }
+    if required <= capacity { return Some(capacity); }
+
+    Some(required.checked_next_power_of_two().map_or(max, |capacity| capacity.min(max)))
+}
+
+fn extend_dirty_range(dirty: &mut Option<Range<usize>>, range: Range<usize>) {
+    if range.is_empty() { return; }
+
+    *dirty = match dirty.take() {
+        Some(current) => Some(current.start.min(range.start)..current.end.max(range.end)),
+        None => Some(range),
+    };
+}
+
+/// Grows range until it covers whole wgpu::COPY_BUFFER_ALIGNMENT chunks of T, without running past len.
+fn align_copy_range<T>(range: Range<usize>, len: usize) -> Range<usize> {
+    let alignment = wgpu::COPY_BUFFER_ALIGNMENT as usize;
+    let per_chunk = usize::max(alignment / size_of::<T>(), 1);
+
+    let start = range.start - (range.start % per_chunk);
+    let end = usize::min(range.end.div_ceil(per_chunk) * per_chunk, len);
+
+    start..end
+}
+
 impl<V: Vertex, I: Index> Buffer for GeometryBuffer<V, I> {
     fn reset(&mut self) {
-         #[cfg(debug_assertions)] {
+        self.cache.reset();
+    }
+
+    fn vertex_count(&self) -> usize {
+        self.cache.vertex_count()
+    }
+
+    fn vertex_capacity(&self) -> usize {
+        self.cache.vertex_capacity()
+    }
+
+    fn remaining_vertex_capacity(&self) -> usize {
+        self.cache.remaining_vertex_capacity()
+    }
+
+    fn index_count(&self) -> usize {
+        self.cache.index_count()
+    }
+
+    fn index_capacity(&self) -> usize {
+        self.cache.index_capacity()
+    }
+
+    fn remaining_index_capacity(&self) -> usize {
+        self.cache.remaining_index_capacity()
+    }
+}
+
+impl<V: Vertex, I: Index> Buffer for GeometryCache<V, I> {
+    fn reset(&mut self) {
+        #[cfg(debug_assertions)] {
             if self.pending_writes {
                 log::warn!("Clearing buffer with pending writes.");
             }
         }
 
-        self.index_cache.clear();
-        self.vertex_cache.clear();
+        self.indices.clear();
+        self.vertices.clear();
 
         self.over_capacity = false;
         self.pending_writes = false;
+        self.dirty_vertices = None;
+        self.dirty_indices = None;
     }
 
     fn vertex_count(&self) -> usize {
-        self.vertex_cache.len()
+        self.vertices.len()
     }
 
     fn vertex_capacity(&self) -> usize {
@@ -137,7 +366,7 @@ impl<V: Vertex, I: Index> Buffer for GeometryBuffer<V, I> {
     }
 
     fn index_count(&self) -> usize {
-        self.index_cache.len()
+        self.indices.len()
     }
 
     fn index_capacity(&self) -> usize {
@@ -155,9 +384,25 @@ where
     I: Index
 {
     fn write_geometry_buffer(&self, buffer: &mut GeometryBuffer<V, I>) {
-        buffer.pending_writes = false;
-        self.write_buffer(&buffer.vertex_buffer, 0, bytemuck::cast_slice(&buffer.vertex_cache));
-        self.write_buffer(&buffer.index_buffer, 0, bytemuck::cast_slice(&buffer.index_cache));
+        let cache = &mut buffer.cache;
+        if cache.gpu_buffers_stale {
+            log::warn!("Skipping write to {}, it grew without being reallocated.", buffer.label);
+            return;
+        }
+
+        cache.pending_writes = false;
+
+        if let Some(dirty) = cache.dirty_vertices.take() {
+            let range = align_copy_range::<V>(dirty, cache.vertices.len());
+            let offset = (range.start * size_of::<V>()) as wgpu::BufferAddress;
+            self.write_buffer(&buffer.vertex_buffer, offset, bytemuck::cast_slice(&cache.vertices[range]));
+        }
+
+        if let Some(dirty) = cache.dirty_indices.take() {
+            let range = align_copy_range::<I>(dirty, cache.indices.len());
+            let offset = (range.start * size_of::<I>()) as wgpu::BufferAddress;
+            self.write_buffer(&buffer.index_buffer, offset, bytemuck::cast_slice(&cache.indices[range]));
+        }
     }
 }
 
@@ -169,13 +414,136 @@ where
 {
     fn draw_geometry_buffer(&mut self, buffer: &'b GeometryBuffer<V, I>) {
         #[cfg(debug_assertions)] {
-            if buffer.pending_writes {
+            if buffer.cache.pending_writes {
                 log::warn!("Drawing buffer with pending writes.");
             }
         }
 
         self.set_vertex_buffer(0, buffer.vertex_buffer.slice(..));
-        self.set_index_buffer(buffer.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-        self.draw_indexed(0..buffer.index_cache.len() as u32, 0, 0..1);
+        self.set_index_buffer(buffer.index_buffer.slice(..), I::index_format());
+        self.draw_indexed(0..buffer.cache.indices.len() as u32, 0, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use bytemuck::Zeroable;
+
+    use super::super::super::vertex::ColoredVertex;
+
+    fn quad(x: f32) -> [ColoredVertex; 4] {
+        [0.0, 1.0, 2.0, 3.0].map(|y| ColoredVertex { position: [x, y, 0.0], color: [1.0; 4], rect: [0.0; 4], corner_radius: 0.0 })
+    }
+
+    #[test]
+    fn merging_rebases_the_appended_indices() {
+        let mut cache = GeometryCache::<ColoredVertex, u16>::new(4, 6);
+        cache.push_quad(quad(0.0));
+        let mut other = GeometryCache::<ColoredVertex, u16>::new(4, 6);
+        other.push_quad(quad(1.0));
+
+        cache.merge(&other).unwrap();
+
+        let xs = cache.vertices.iter().map(|v| v.position[0]).collect::<Vec<_>>();
+        assert_eq!(xs, [0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(cache.indices, [0, 1, 2, 2, 3, 0, 4, 5, 6, 6, 7, 4]);
+        assert_eq!(cache.dirty_indices, Some(0..12));
+    }
+
+    #[test]
+    fn merging_past_what_the_indices_can_address_fails() {
+        let mut cache = GeometryCache::<ColoredVertex, u16>::new(4, 6);
+        cache.push_geometry(&vec![ColoredVertex::zeroed(); u16::MAX as usize - 1], &[]);
+        let mut other = GeometryCache::<ColoredVertex, u16>::new(4, 6);
+        other.push_quad(quad(1.0));
+
+        assert!(cache.merge(&other).is_err());
+        assert_eq!(cache.indices.len(), 0);
+    }
+
+    #[test]
+    fn grown_capacity_doubles_up_to_the_index_limit() {
+        assert_eq!(grown_capacity(16, 10, 65536), Some(16));
+        assert_eq!(grown_capacity(16, 17, 65536), Some(32));
+        assert_eq!(grown_capacity(16, 1000, 65536), Some(1024));
+        assert_eq!(grown_capacity(32000, 40000, 65536), Some(65536));
+        assert_eq!(grown_capacity(32000, 65537, 65536), None);
+    }
+
+    #[test]
+    fn modifying_one_quad_only_marks_its_vertices_dirty() {
+        let mut cache = GeometryCache::<ColoredVertex, u16>::new(12, 18);
+        for x in 0..3 {
+            cache.push_quad(quad(x as f32));
+        }
+        // As if write_geometry_buffer just uploaded everything.
+        cache.dirty_vertices = None;
+        cache.dirty_indices = None;
+
+        for vertex in cache.quad_vertices_mut(1).unwrap() {
+            vertex.color = [0.0; 4];
+        }
+        assert_eq!(cache.dirty_vertices, Some(4..8));
+        assert_eq!(cache.dirty_indices, None);
+        assert_eq!(cache.get(4).unwrap().color, [0.0; 4]);
+
+        assert!(cache.quad_vertices_mut(3).is_none());
+        assert_eq!(cache.dirty_vertices, Some(4..8));
+    }
+
+    #[test]
+    fn dirty_ranges_grow_to_cover_every_edit() {
+        let mut dirty = None;
+        extend_dirty_range(&mut dirty, 8..12);
+        assert_eq!(dirty, Some(8..12));
+
+        extend_dirty_range(&mut dirty, 10..10);
+        assert_eq!(dirty, Some(8..12));
+
+        extend_dirty_range(&mut dirty, 20..24);
+        assert_eq!(dirty, Some(8..24));
+    }
+
+    #[test]
+    fn copy_ranges_cover_whole_alignment_chunks() {
+        // 4 byte alignment, so u16 indices go up in pairs and anything 4 bytes or wider is already aligned.
+        assert_eq!(align_copy_range::<u16>(3..6, 100), 2..6);
+        assert_eq!(align_copy_range::<u16>(3..6, 5), 2..5);
+        assert_eq!(align_copy_range::<u32>(3..6, 100), 3..6);
+        assert_eq!(align_copy_range::<[f32; 5]>(8..12, 100), 8..12);
+    }
+
+    #[test]
+    fn lines_take_two_indices_quads_take_six() {
+        // A line pushed after one quad's worth of vertices.
+        assert_eq!(line_indices::<u16>(4), [4, 5]);
+        assert_eq!(quad_indices::<u16>(4), [4, 5, 6, 6, 7, 4]);
+    }
+
+    #[test]
+    fn fixed_buffers_drop_what_doesnt_fit() {
+        let mut cache = GeometryCache::<ColoredVertex, u16>::new(4, 6);
+        cache.set_growth_policy(GrowthPolicy::Fixed);
+
+        cache.push_quad(quad(0.0));
+        assert!(!cache.over_capacity);
+
+        cache.push_quad(quad(1.0));
+        assert!(cache.over_capacity);
+        assert_eq!((cache.vertex_count(), cache.index_count()), (4, 6));
+        assert_eq!(cache.vertex_capacity(), 4);
+
+        let mut other = GeometryCache::<ColoredVertex, u16>::new(4, 6);
+        other.push_quad(quad(2.0));
+        assert!(cache.merge(&other).is_err());
+
+        // Growing is the default.
+        let mut cache = GeometryCache::<ColoredVertex, u16>::new(4, 6);
+        cache.push_quad(quad(0.0));
+        cache.push_quad(quad(1.0));
+        assert!(!cache.over_capacity);
+        assert_eq!((cache.vertex_count(), cache.vertex_capacity()), (8, 8));
     }
 }
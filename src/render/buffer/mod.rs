@@ -2,7 +2,7 @@ use std::ops::Add;
 
 mod geometry_buffer;
 
-pub use geometry_buffer::GeometryBuffer;
+pub use geometry_buffer::{GeometryBuffer, GrowthPolicy};
 use super::vertex::Vertex;
 
 pub trait Buffer {
@@ -44,16 +44,37 @@ where
 }
 
 pub trait Index: bytemuck::Pod + Add {
+    const MAX: usize;
+
+    fn index_format() -> wgpu::IndexFormat;
     fn from_usize(src: usize) -> Self;
     fn as_usize(self) -> usize;
 }
 
 impl Index for u16 {
+    const MAX: usize = u16::MAX as usize;
+
+    fn index_format() -> wgpu::IndexFormat { wgpu::IndexFormat::Uint16 }
     fn from_usize(src: usize) -> Self { src as Self }
     fn as_usize(self) -> usize { self as usize }
 }
 
 impl Index for u32 {
+    const MAX: usize = u32::MAX as usize;
+
+    fn index_format() -> wgpu::IndexFormat { wgpu::IndexFormat::Uint32 }
     fn from_usize(src: usize) -> Self { src as Self }
     fn as_usize(self) -> usize { self as usize }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_format_matches_the_index_width() {
+        assert_eq!(u16::index_format(), wgpu::IndexFormat::Uint16);
+        assert_eq!(u32::index_format(), wgpu::IndexFormat::Uint32);
+        assert_eq!(u32::from_usize(70_000).as_usize(), 70_000);
+    }
+}
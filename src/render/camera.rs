@@ -5,6 +5,10 @@ pub struct Camera {
     pub aspect_ratio: f32,
     pub position: cgmath::Point3<f32>,
     pub y_axis_dim: f32,
+
+    // Snap zoom and position so sprite texels land on whole pixels, avoids shimmer when zooming pixel art.
+    pub pixel_snap: bool,
+    pub texels_per_tile: f32,
 }
 
 impl Camera {
@@ -12,6 +16,43 @@ impl Camera {
         self.aspect_ratio = physical_size.width as f32 / physical_size.height as f32;
         self.position = src.position;
         self.y_axis_dim = src.zoom_level;
+
+        if self.pixel_snap {
+            self.snap_to_texels(physical_size.height);
+        }
+    }
+
+    /// Nudges zoom so a texel covers a whole number of pixels (or a pixel a whole number of texels) and moves the
+    /// camera onto a pixel boundary.
+    fn snap_to_texels(&mut self, window_height: u32) {
+        if window_height == 0 { return; }
+        let window_height = window_height as f32;
+
+        let pixels_per_texel = window_height / (self.y_axis_dim * self.texels_per_tile);
+        let snapped_pixels_per_texel = if pixels_per_texel >= 1.0 {
+            pixels_per_texel.round()
+        } else {
+            1.0 / (1.0 / pixels_per_texel).round()
+        };
+
+        self.y_axis_dim = window_height / (snapped_pixels_per_texel * self.texels_per_tile);
+
+        let world_per_pixel = self.y_axis_dim / window_height;
+        self.position.x = (self.position.x / world_per_pixel).round() * world_per_pixel;
+        self.position.y = (self.position.y / world_per_pixel).round() * world_per_pixel;
+    }
+
+    /// World space (min, max) corners of the area currently visible through the camera.
+    pub fn world_view_rect(&self, window_size: winit::dpi::PhysicalSize<u32>) -> ((f32, f32), (f32, f32)) {
+        let aspect_ratio = window_size.width as f32 / window_size.height as f32;
+
+        let y_radius = self.y_axis_dim / 2.0;
+        let x_radius = y_radius * aspect_ratio;
+
+        let min = (self.position.x - x_radius, self.position.y - y_radius);
+        let max = (self.position.x + x_radius, self.position.y + y_radius);
+
+        (min, max)
     }
 }
 
@@ -104,3 +145,43 @@ fn ortho_matrix(left: f32, right: f32, bottom: f32, top: f32, near: f32, far: f3
 
     OPENGL_TO_WGPU_MATRIX * ortho
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn camera(y_axis_dim: f32) -> Camera {
+        Camera {
+            aspect_ratio: 1.0,
+            position: cgmath::Point3::new(10.0, 5.0, -0.5),
+            y_axis_dim,
+            pixel_snap: false,
+            texels_per_tile: 32.0,
+        }
+    }
+
+    #[test]
+    fn world_view_rect_spans_the_visible_area() {
+        let window_size = winit::dpi::PhysicalSize::new(1600, 900);
+        let ((min_x, min_y), (max_x, max_y)) = camera(9.0).world_view_rect(window_size);
+
+        assert!(((max_x - min_x) - 9.0 * (1600.0 / 900.0)).abs() < 1e-4);
+        assert!(((max_y - min_y) - 9.0).abs() < 1e-4);
+        assert!((((min_x + max_x) / 2.0) - 10.0).abs() < 1e-4);
+        assert!((((min_y + max_y) / 2.0) - 5.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn snapped_zoom_lands_texels_on_whole_pixels() {
+        let window_height = 900;
+
+        for zoom in [1.7, 4.3, 9.0, 27.5, 80.0] {
+            let mut camera = camera(zoom);
+            camera.snap_to_texels(window_height);
+
+            let pixels_per_texel = window_height as f32 / (camera.y_axis_dim * camera.texels_per_tile);
+            let whole = if pixels_per_texel >= 1.0 { pixels_per_texel } else { 1.0 / pixels_per_texel };
+            assert!((whole - whole.round()).abs() < 1e-3, "zoom {} gives {} pixels per texel", zoom, pixels_per_texel);
+        }
+    }
+}
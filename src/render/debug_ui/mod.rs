@@ -1,3 +1,5 @@
 mod buffer_usage_meter;
+mod theme;
 
 pub use buffer_usage_meter::BufferUsageMeter;
+pub use theme::DebugUiTheme;
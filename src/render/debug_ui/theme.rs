@@ -0,0 +1,53 @@
+pub type Color = (f32, f32, f32, f32);
+
+#[derive(Debug, Clone, Copy)]
+pub struct DebugUiTheme {
+    pub background_color: Color,
+    // Pixels, 0 => sharp corners.
+    pub panel_corner_radius: f32,
+    pub bar_color: Color,
+    // Usage fractions (0.0..1.0) above which the bars switch color.
+    pub warning_threshold: f32,
+    pub warning_color: Color,
+    pub critical_threshold: f32,
+    pub critical_color: Color,
+}
+
+impl DebugUiTheme {
+    pub fn usage_color(&self, usage_perc: f32) -> Color {
+        match usage_perc {
+            x if x > self.critical_threshold => self.critical_color,
+            x if x > self.warning_threshold  => self.warning_color,
+            _ => self.bar_color,
+        }
+    }
+}
+
+impl Default for DebugUiTheme {
+    fn default() -> Self {
+        Self {
+            background_color: (0.5, 0.5, 0.5, 0.3),
+            panel_corner_radius: 0.0,
+            bar_color: (1.0, 1.0, 1.0, 0.7),
+            warning_threshold: 0.75,
+            warning_color: (0.5, 0.5, 0.2, 0.7),
+            critical_threshold: 0.90,
+            critical_color: (0.6, 0.1, 0.1, 0.7),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lowering_the_warning_threshold_recolors_the_bar() {
+        let default = DebugUiTheme::default();
+        let custom = DebugUiTheme { warning_threshold: 0.5, ..default };
+
+        assert_eq!(default.usage_color(0.6), default.bar_color);
+        assert_eq!(custom.usage_color(0.6), custom.warning_color);
+        assert_eq!(custom.usage_color(0.95), custom.critical_color);
+    }
+}
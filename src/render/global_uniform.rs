@@ -0,0 +1,39 @@
+/// Per frame values shared by every pipeline that binds the global bind group.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GlobalUniform {
+    pub time_s: f32,
+    // vec2 members are 8 byte aligned in uniform buffers.
+    _padding: f32,
+    // World space distance the tip of a fully swaying sprite leans at the peak of a gust.
+    pub wind: [f32; 2],
+    // Multiplies every tile and entity color, darkens the scene at night.
+    pub tint: [f32; 4],
+}
+
+impl GlobalUniform {
+    pub fn new(time: std::time::Duration, wind: (f32, f32), ambient_light: f32) -> Self {
+        Self {
+            time_s: time.as_secs_f32(),
+            _padding: 0.0,
+            wind: [wind.0, wind.1],
+            tint: [ambient_light, ambient_light, ambient_light, 1.0],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_leads_the_uploaded_bytes() {
+        let uniform = GlobalUniform::new(std::time::Duration::from_millis(2500), (0.25, -0.5), 0.8);
+        let bytes: &[u8] = bytemuck::bytes_of(&uniform);
+
+        assert_eq!(bytes[0..4], 2.5f32.to_ne_bytes());
+        assert_eq!(bytes[8..12], 0.25f32.to_ne_bytes());
+        // Uniform buffers are bound in 16 byte chunks.
+        assert_eq!(bytes.len() % 16, 0);
+    }
+}
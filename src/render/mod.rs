@@ -1,13 +1,16 @@
 mod buffer;
 mod buffer_usages;
 mod camera;
+mod capture;
 mod debug_ui;
+mod global_uniform;
 mod quad;
 mod render_state;
 mod sprite_sheet;
 mod texture;
 mod utils;
 mod vertex;
+mod z_band;
 
-pub use render_state::RenderState;
-pub use sprite_sheet::{SpriteId, SpriteSheetLayout, SpriteSheetEntry, SpriteSetIdentifier};
+pub use render_state::{RenderState, ShaderId};
+pub use sprite_sheet::{SpriteId, SpriteSheet, SpriteSheetLayout, SpriteSheetEntry, SpriteSetIdentifier};
@@ -1,9 +1,11 @@
-use super::vertex::{TexturedVertex, UvVertex, ColoredVertex};
+use super::vertex::{TexturedVertex, ColoredVertex};
+use super::z_band::UI_Z;
 
 pub struct TexturedQuad {
     pub pos: (f32, f32, f32),
     pub dim: (f32, f32),
     pub tex_index: i32,
+    pub tint: (f32, f32, f32, f32),
 }
 
 impl From<TexturedQuad> for [TexturedVertex; 4] {
@@ -13,22 +15,26 @@ impl From<TexturedQuad> for [TexturedVertex; 4] {
         let x_max = src.pos.0 + src.dim.0;
         let y_max = src.pos.1 + src.dim.1;
         let z = src.pos.2;
+        let tint = [src.tint.0, src.tint.1, src.tint.2, src.tint.3];
 
         [
-            TexturedVertex { position: [x_max, y_max, z], tex_coords: [1.0, 0.0], tex_index: src.tex_index },
-            TexturedVertex { position: [x_min, y_max, z], tex_coords: [0.0, 0.0], tex_index: src.tex_index },
-            TexturedVertex { position: [x_min, y_min, z], tex_coords: [0.0, 1.0], tex_index: src.tex_index },
-            TexturedVertex { position: [x_max, y_min, z], tex_coords: [1.0, 1.0], tex_index: src.tex_index },
+            TexturedVertex { position: [x_max, y_max, z], tex_coords: [1.0, 0.0], tex_index: src.tex_index, tint, sway: 0.0 },
+            TexturedVertex { position: [x_min, y_max, z], tex_coords: [0.0, 0.0], tex_index: src.tex_index, tint, sway: 0.0 },
+            TexturedVertex { position: [x_min, y_min, z], tex_coords: [0.0, 1.0], tex_index: src.tex_index, tint, sway: 0.0 },
+            TexturedVertex { position: [x_max, y_min, z], tex_coords: [1.0, 1.0], tex_index: src.tex_index, tint, sway: 0.0 },
         ]
     }
 }
 
 pub struct TexturedUvQuad {
-    pub pos: (f32, f32),
+    pub pos: (f32, f32, f32),
     pub dim: (f32, f32),
     pub uv_min: (f32, f32),
     pub uv_max: (f32, f32),
     pub tex_index: i32,
+    pub tint: (f32, f32, f32, f32),
+    // Wind sway at the quad's top edge, the bottom edge never moves.
+    pub sway: f32,
 }
 
 impl From<TexturedUvQuad> for [TexturedVertex; 4] {
@@ -37,55 +43,94 @@ impl From<TexturedUvQuad> for [TexturedVertex; 4] {
         let y_min = src.pos.1;
         let x_max = src.pos.0 + src.dim.0;
         let y_max = src.pos.1 + src.dim.1;
+        let z = src.pos.2;
+        let tint = [src.tint.0, src.tint.1, src.tint.2, src.tint.3];
 
         [
-            TexturedVertex { position: [x_max, y_max, 0.0], tex_coords: [src.uv_max.0, src.uv_min.1], tex_index: src.tex_index },
-            TexturedVertex { position: [x_min, y_max, 0.0], tex_coords: [src.uv_min.0, src.uv_min.1], tex_index: src.tex_index },
-            TexturedVertex { position: [x_min, y_min, 0.0], tex_coords: [src.uv_min.0, src.uv_max.1], tex_index: src.tex_index },
-            TexturedVertex { position: [x_max, y_min, 0.0], tex_coords: [src.uv_max.0, src.uv_max.1], tex_index: src.tex_index },
+            TexturedVertex { position: [x_max, y_max, z], tex_coords: [src.uv_max.0, src.uv_min.1], tex_index: src.tex_index, tint, sway: src.sway },
+            TexturedVertex { position: [x_min, y_max, z], tex_coords: [src.uv_min.0, src.uv_min.1], tex_index: src.tex_index, tint, sway: src.sway },
+            TexturedVertex { position: [x_min, y_min, z], tex_coords: [src.uv_min.0, src.uv_max.1], tex_index: src.tex_index, tint, sway: src.sway },
+            TexturedVertex { position: [x_max, y_min, z], tex_coords: [src.uv_max.0, src.uv_max.1], tex_index: src.tex_index, tint, sway: src.sway },
         ]
     }
 }
 
-pub struct UntexturedQuad {
+pub struct ColoredQuad {
     pub pos: (f32, f32),
     pub dim: (f32, f32),
+    pub color: (f32, f32, f32, f32),
+    // 0 => sharp corners.
+    pub corner_radius: f32,
 }
 
-impl From<UntexturedQuad> for [UvVertex; 4] {
-    fn from(src: UntexturedQuad) -> Self {
+impl From<ColoredQuad> for [ColoredVertex; 4] {
+    fn from(src: ColoredQuad) -> Self {
         let x_min = src.pos.0;
         let y_min = src.pos.1;
         let x_max = src.pos.0 + src.dim.0;
         let y_max = src.pos.1 + src.dim.1;
 
+        let color = [src.color.0, src.color.1, src.color.2, src.color.3];
+        let (half_x, half_y) = (src.dim.0 * 0.5, src.dim.1 * 0.5);
+        // Can't round more than half the shortest side.
+        let corner_radius = src.corner_radius.clamp(0.0, f32::min(half_x, half_y));
+
         [
-            UvVertex { position: [x_max, y_max, 0.0], uv: [1.0, 0.0] },
-            UvVertex { position: [x_min, y_max, 0.0], uv: [0.0, 0.0] },
-            UvVertex { position: [x_min, y_min, 0.0], uv: [0.0, 1.0] },
-            UvVertex { position: [x_max, y_min, 0.0], uv: [1.0, 1.0] },
+            ColoredVertex { position: [x_max, y_max, UI_Z], color, rect: [ half_x,  half_y, half_x, half_y], corner_radius },
+            ColoredVertex { position: [x_min, y_max, UI_Z], color, rect: [-half_x,  half_y, half_x, half_y], corner_radius },
+            ColoredVertex { position: [x_min, y_min, UI_Z], color, rect: [-half_x, -half_y, half_x, half_y], corner_radius },
+            ColoredVertex { position: [x_max, y_min, UI_Z], color, rect: [ half_x, -half_y, half_x, half_y], corner_radius },
         ]
     }
 }
 
-pub struct ColoredQuad {
-    pub pos: (f32, f32),
+/// A world space rect whose edges fade out over the last pixel, for lines that should stay crisp at any zoom.
+pub struct LineQuad {
+    pub pos: (f32, f32, f32),
     pub dim: (f32, f32),
     pub color: (f32, f32, f32, f32),
+    pub pixels_per_unit: f32,
 }
 
-impl From<ColoredQuad> for [ColoredVertex; 4] {
-    fn from(src: ColoredQuad) -> Self {
+impl From<LineQuad> for [ColoredVertex; 4] {
+    fn from(src: LineQuad) -> Self {
         let x_min = src.pos.0;
         let y_min = src.pos.1;
         let x_max = src.pos.0 + src.dim.0;
         let y_max = src.pos.1 + src.dim.1;
+        let z = src.pos.2;
+
+        let color = [src.color.0, src.color.1, src.color.2, src.color.3];
+        // The ui shader fades edges in rect units, measure the rect in pixels so the fade is a pixel wide.
+        let (half_x, half_y) = (src.dim.0 * 0.5 * src.pixels_per_unit, src.dim.1 * 0.5 * src.pixels_per_unit);
+        let corner_radius = f32::min(0.5, f32::min(half_x, half_y));
 
         [
-            ColoredVertex { position: [x_max, y_max, 0.0], color: [src.color.0, src.color.1, src.color.2, src.color.3] },
-            ColoredVertex { position: [x_min, y_max, 0.0], color: [src.color.0, src.color.1, src.color.2, src.color.3] },
-            ColoredVertex { position: [x_min, y_min, 0.0], color: [src.color.0, src.color.1, src.color.2, src.color.3] },
-            ColoredVertex { position: [x_max, y_min, 0.0], color: [src.color.0, src.color.1, src.color.2, src.color.3] },
+            ColoredVertex { position: [x_max, y_max, z], color, rect: [ half_x,  half_y, half_x, half_y], corner_radius },
+            ColoredVertex { position: [x_min, y_max, z], color, rect: [-half_x,  half_y, half_x, half_y], corner_radius },
+            ColoredVertex { position: [x_min, y_min, z], color, rect: [-half_x, -half_y, half_x, half_y], corner_radius },
+            ColoredVertex { position: [x_max, y_min, z], color, rect: [ half_x, -half_y, half_x, half_y], corner_radius },
         ]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn panel(corner_radius: f32) -> [ColoredVertex; 4] {
+        ColoredQuad { pos: (5.0, 10.0), dim: (100.0, 20.0), color: (0.5, 0.5, 0.5, 0.3), corner_radius }.into()
+    }
+
+    #[test]
+    fn corner_radius_reaches_every_vertex() {
+        let vertices = panel(4.0);
+        assert!(vertices.iter().all(|v| v.corner_radius == 4.0 && v.rect[2..] == [50.0, 10.0]));
+
+        // Clamped to half the shortest side.
+        assert!(panel(25.0).iter().all(|v| v.corner_radius == 10.0));
+
+        // Radius 0 skips the shader's rounding, the panel keeps its sharp corners and flat color.
+        assert!(panel(0.0).iter().all(|v| v.corner_radius == 0.0 && v.color == [0.5, 0.5, 0.5, 0.3]));
+    }
+}
@@ -2,28 +2,37 @@ use std::mem::size_of;
 use std::time::Duration;
 
 use anyhow::Result;
-use log::debug;
+use log::{debug, error};
 use winit::window::Window;
 
 use crate::debug::DebugTimers;
-use crate::game::game_state::{self, GameState, GroundCover, SoilType};
-use crate::game::{TileType, self};
+use crate::game::game_state::{self, GameState, SeedDispersal, SoilType};
+use crate::game::{SpeciesConfig, TileType, Tree, TreeGrowthStage, WorldPosition};
 use crate::timer::{TimerState, Timer};
 use crate::timer::measure;
 
-use super::debug_ui::BufferUsageMeter;
+use super::debug_ui::{BufferUsageMeter, DebugUiTheme};
 
-use super::buffer::{Buffer, GeometryBuffer, DrawGeometryBuffer, WriteGeometryBuffer};
+use super::buffer::{Buffer, GeometryBuffer, GrowthPolicy, DrawGeometryBuffer, WriteGeometryBuffer};
 use super::buffer_usages::BufferUsages;
 use super::camera::{Camera, CameraUniform};
-use super::quad::{TexturedQuad, TexturedUvQuad, UntexturedQuad, ColoredQuad};
+use super::capture::FrameCapture;
+use super::global_uniform::GlobalUniform;
+use super::quad::{TexturedQuad, TexturedUvQuad, ColoredQuad, LineQuad};
 use super::sprite_sheet::{SpriteSheet};
-use super::texture::Texture;
-use super::utils::gpu::{ create_buffer, create_shader_module, create_render_pipeline };
-use super::vertex::{Vertex, TexturedVertex, UvVertex, ColoredVertex};
+use super::texture::{SamplerConfig, Texture};
+use super::utils::gpu::{ create_buffer, create_shader_module, create_render_pipeline, validate_wgsl, PipelineOutput };
+use super::vertex::{Vertex, TexturedVertex, ColoredVertex};
+use super::z_band::{self, GROUND_Z_DIRT, GROUND_Z_GRASS, GROUND_Z_STONE, GROUND_Z_GRID, OVERLAY_Z, UI_Z};
+
+// Burning trees are drawn scorched orange.
+const BURNING_TREE_TINT: (f32, f32, f32, f32) = (1.0, 0.45, 0.2, 1.0);
+// Not a sprite, main_shader draws quads with this tex_index as a soft round shadow.
+const SHADOW_TEX_INDEX: i32 = -1;
 
 pub struct RenderState {
     window_size: winit::dpi::PhysicalSize<u32>,
+    cursor_position: Option<winit::dpi::PhysicalPosition<f64>>,
     camera: Camera,
 
     instance: wgpu::Instance,
@@ -35,10 +44,13 @@ pub struct RenderState {
     device: wgpu::Device,
     queue: wgpu::Queue,
 
-    tile_quad_buffer: GeometryBuffer<TexturedVertex, u16>,
-    shadow_quad_buffer: GeometryBuffer<UvVertex, u16>,
-    entity_quad_buffer: GeometryBuffer<TexturedVertex, u16>,
+    // Scales with the grid size, u16 indices run out around 16k quads.
+    tile_quad_buffer: GeometryBuffer<TexturedVertex, u32>,
+    // Trees and their shadows, up to two quads per tree.
+    entity_quad_buffer: GeometryBuffer<TexturedVertex, u32>,
     ui_quad_buffer: GeometryBuffer<ColoredVertex, u16>,
+    debug_line_buffer: GeometryBuffer<ColoredVertex, u16>,
+    grid_line_buffer: GeometryBuffer<ColoredVertex, u16>,
 
     sprite_sheet: SpriteSheet<TileType>,
     tile_sprite_sheet: Texture,
@@ -47,21 +59,51 @@ pub struct RenderState {
     // This is wrapped in an options JUST so we can "take" it out of self and then use &mut self. Because the borrow checker is a pain.
     depth_texture: Option<Texture>,
 
+    // The scene is rendered at window_size * render_scale into scene_target, then stretched over the surface.
+    // No scene_target when the scale is 1, we render straight to the surface.
+    render_scale: f32,
+    scene_target: Option<SceneTarget>,
+    capture_requested: bool,
+    captured_frame: Option<image::RgbaImage>,
+
     camera_buffer: wgpu::Buffer,
     camera_bind_group: wgpu::BindGroup,
 
     ui_camera_buffer: wgpu::Buffer,
     ui_camera_bind_group: wgpu::BindGroup,
 
+    global_buffer: wgpu::Buffer,
+    global_bind_group: wgpu::BindGroup,
+
     clear_color: [f64; 3],
-    tile_render_pipeline: wgpu::RenderPipeline,
-    entity_render_pipeline: wgpu::RenderPipeline,
-    shadow_render_pipeline: wgpu::RenderPipeline,
-    ui_render_pipeline: wgpu::RenderPipeline,
+    frame_budget: Duration,
+    pub highlight_hovered_tile: bool,
+    // Max shadow quads per frame, when exceeded only the biggest shadows are drawn.
+    pub shadow_budget: Option<usize>,
+    // Extra world space around the view in which trees are still drawn.
+    pub tree_cull_margin: f32,
+    // When a tile is drawn smaller than this many pixels, only the biggest tree on each tile is drawn. Only reachable on
+    // grids big enough that GameState::max_zoom lets the camera out that far.
+    pub tree_lod_min_pixels: f32,
+    // Trees whose bases fall within the same strip this tall are layered by canopy size rather than y. 0 disables.
+    pub understory_y_range: f32,
+    // How much darker fully shaded ground is drawn, previews grass about to die off. 0 disables.
+    pub ground_shade_strength: f32,
+    // Inset ground uvs by half a texel, hides seams between adjacent tiles when minified.
+    pub ground_uv_inset: bool,
+    // Pixel width of the lines drawn by show_grid, constant at any zoom. None => the GridLine sprite, which vanishes when zoomed out.
+    pub grid_line_width: Option<f32>,
+    // World space lean of a fully swaying tree crown at the peak of a gust. (0, 0) disables.
+    pub wind: (f32, f32),
+    pub debug_ui_theme: DebugUiTheme,
+
+    shaders: ShaderModules,
+    bind_group_layouts: BindGroupLayouts,
+    pipelines: RenderPipelines,
 }
 
 impl RenderState {
-    pub async fn new(window: &Window, game_state: &GameState) -> Self {
+    pub async fn new(window: &Window, game_state: &GameState, frame_budget: Duration) -> Self {
         let window_size = window.inner_size();
 
         let instance = wgpu::Instance::new(wgpu::Backends::all());
@@ -88,26 +130,18 @@ impl RenderState {
 
         debug!("Configuring surface.");
 
-        let surface_config = wgpu::SurfaceConfiguration {
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-            format: surface.get_preferred_format(&adapter).unwrap(), //NOTE: Unwrap is safe here, we've created the adapter to specifically support the surface.
-            width: window_size.width,
-            height: window_size.height,
-
-            // present_mode:
-            //  Fifo      => VScync.
-            //  Mailbox   => Submit eagerly, VScync, fallback to Fifo if unsupported.
-            //  Immediate => Low latency, no wait for display, tearing may be observed.
-            present_mode: wgpu::PresentMode::Mailbox,
-        };
+        //NOTE: Unwrap is safe here, we've created the adapter to specifically support the surface.
+        let surface_config = initial_surface_config(surface.get_preferred_format(&adapter).unwrap(), window_size);
 
         surface.configure(&device, &surface_config);
 
         debug!("Compiling shaders...");
 
-        let main_shader = create_shader_module(&device, "render_state -> main_shader", include_str!("../../res/shaders/main_shader.wgsl"));
-        let circle_shader = create_shader_module(&device, "render_state -> circle_shader", include_str!("../../res/shaders/circle_shader.wgsl"));
-        let ui_shader = create_shader_module(&device, "render_state -> ui_shader", include_str!("../../res/shaders/debug_ui_shader.wgsl"));
+        let shaders = ShaderModules {
+            main_shader: create_shader_module(&device, "render_state -> main_shader", include_str!("../../res/shaders/main_shader.wgsl")),
+            ui_shader: create_shader_module(&device, "render_state -> ui_shader", include_str!("../../res/shaders/debug_ui_shader.wgsl")),
+            blit_shader: create_shader_module(&device, "render_state -> blit_shader", include_str!("../../res/shaders/blit_shader.wgsl")),
+        };
 
         debug!("Creating depth buffer...");
         let depth_texture = Texture::create_depth_texture("render_state.depth_texture", &device, surface_config.width, surface_config.height);
@@ -115,7 +149,7 @@ impl RenderState {
         debug!("Loading textures...");
 
         let sprite_sheet_bytes = include_bytes!("../../res/textures/tile_sprite_sheet.png");
-        let tile_sprite_sheet = Texture::try_from_bytes(Some("Goose Texture"), sprite_sheet_bytes, &device, &queue).unwrap();
+        let tile_sprite_sheet = Texture::try_from_bytes(Some("Goose Texture"), sprite_sheet_bytes, SamplerConfig::PixelArt, &device, &queue).unwrap();
 
         let sprite_sheet_layout = crate::game::get_sprite_sheet_layout();
         let sprite_sheet = SpriteSheet::try_load_from_bytes(sprite_sheet_bytes, &sprite_sheet_layout, &device, &queue).unwrap();
@@ -123,14 +157,19 @@ impl RenderState {
         debug!("Creating buffers...");
 
         let tile_quad_buffer = GeometryBuffer::new_with_quad_capacity(&device, "render_state.tile_quad_buffer", 8000);
-        let shadow_quad_buffer = GeometryBuffer::new_with_quad_capacity(&device, "render_state.shadow_quad_buffer", 8000);
-        let entity_quad_buffer = GeometryBuffer::new_with_quad_capacity(&device, "render_state.entity_quad_buffer", 8000);
+        let entity_quad_buffer = GeometryBuffer::new_with_quad_capacity(&device, "render_state.entity_quad_buffer", 16000);
         let ui_quad_buffer = GeometryBuffer::new_with_quad_capacity(&device, "render_state.entity_quad_buffer", 8000);
+        let mut debug_line_buffer = GeometryBuffer::new(&device, "render_state.debug_line_buffer", 20000, 20000);
+        // Overlays like slot indices can draw a lot of lines on a big grid, cap them rather than grow without bound.
+        debug_line_buffer.set_growth_policy(GrowthPolicy::Fixed);
+        let grid_line_buffer = GeometryBuffer::new_with_quad_capacity(&device, "render_state.grid_line_buffer", 512);
 
         let camera = Camera {
             aspect_ratio: 1.0,
             position: cgmath::Point3::new(0.0, 0.0, 1.0),
             y_axis_dim: 5.0,
+            pixel_snap: false,
+            texels_per_tile: sprite_sheet_layout.tile_dim.1 as f32,
         };
 
         let camera_buffer = create_buffer(&device, "render_state.camera_buffer", size_of::<CameraUniform>(), BufferUsages::UniformCopyDst.into());
@@ -224,114 +263,87 @@ impl RenderState {
             }
         );
 
-        debug!("Creating render pipelines...");
-
-        let tile_render_pipeline = {
-            let label = "render_state.tile_render_pipeline";
-            let bind_group_layouts = [&camera_bind_group_layout, &tile_sprite_sheet_bind_group_layout];
-            let push_constant_ranges = [];
-            let buffer_layouts = [TexturedVertex::describe_buffer()];
-
-            let depth_stencil = wgpu::DepthStencilState {
-                format: depth_texture.format,
-                depth_write_enabled: false,
-                depth_compare: wgpu::CompareFunction::Always,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            };
-
-            create_render_pipeline(
-                &device,
-                label,
-                &bind_group_layouts,
-                &push_constant_ranges,
-                &buffer_layouts,
-                surface_config.format,
-                &main_shader,
-                Some(depth_stencil)
-            )
-        };
+        let global_buffer = create_buffer(&device, "render_state.global_buffer", size_of::<GlobalUniform>(), BufferUsages::UniformCopyDst.into());
+        queue.write_buffer(&global_buffer, 0, bytemuck::cast_slice(&[GlobalUniform::new(Duration::ZERO, (0.0, 0.0), 1.0)]));
 
-        let entity_render_pipeline = {
-            let label = "render_state.etity_render_pipeline";
-            let bind_group_layouts = [&camera_bind_group_layout, &tile_sprite_sheet_bind_group_layout];
-            let push_constant_ranges = [];
-            let buffer_layouts = [TexturedVertex::describe_buffer()];
-
-            let depth_stencil = wgpu::DepthStencilState {
-                format: depth_texture.format,
-                depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::LessEqual,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            };
+        let global_bind_group_layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: Some("render_state.global_bind_group -> layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    }
+                ],
+            }
+        );
 
-            create_render_pipeline(
-                &device,
-                label,
-                &bind_group_layouts,
-                &push_constant_ranges,
-                &buffer_layouts,
-                surface_config.format,
-                &main_shader,
-                Some(depth_stencil)
-            )
-        };
+        let global_bind_group = device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: Some("render_state.global_bind_group"),
+                layout: &global_bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: global_buffer.as_entire_binding(),
+                    }
+                ],
+            }
+        );
 
-        let shadow_render_pipeline = {
-            let label = "render_state.shadow_render_pipeline";
-            let bind_group_layouts = [&camera_bind_group_layout];
-            let push_constant_ranges = [];
-            let buffer_layouts = [UvVertex::describe_buffer()];
-
-            let depth_stencil = wgpu::DepthStencilState {
-                format: depth_texture.format,
-                depth_write_enabled: false,
-                depth_compare: wgpu::CompareFunction::Always,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            };
+        let blit_bind_group_layout = device.create_bind_group_layout(
+            &wgpu::BindGroupLayoutDescriptor {
+                label: Some("render_state.blit_bind_group -> layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler {
+                            filtering: true,
+                            comparison: false,
+                        },
+                        count: None,
+                    }
+                ],
+            }
+        );
 
-            create_render_pipeline(
-                &device,
-                label,
-                &bind_group_layouts,
-                &push_constant_ranges,
-                &buffer_layouts,
-                surface_config.format,
-                &circle_shader,
-                Some(depth_stencil)
-            )
+        let bind_group_layouts = BindGroupLayouts {
+            camera: camera_bind_group_layout,
+            tile_sprite_sheet: tile_sprite_sheet_bind_group_layout,
+            global: global_bind_group_layout,
+            blit: blit_bind_group_layout,
         };
 
-        let ui_render_pipeline = {
-            let label = "render_state.debug_ui_render_pipeline";
-            let bind_group_layouts = [&camera_bind_group_layout];
-            let push_constant_ranges = [];
-            let buffer_layouts = [ColoredVertex::describe_buffer()];
-
-            let depth_stencil = wgpu::DepthStencilState {
-                format: depth_texture.format,
-                depth_write_enabled: false,
-                depth_compare: wgpu::CompareFunction::Always,
-                stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
-            };
+        debug!("Creating render pipelines...");
 
-            create_render_pipeline(
-                &device,
-                label,
-                &bind_group_layouts,
-                &push_constant_ranges,
-                &buffer_layouts,
-                surface_config.format,
-                &ui_shader,
-                Some(depth_stencil)
-            )
-        };
+        let pipelines = create_render_pipelines(
+            &device,
+            &shaders,
+            &bind_group_layouts,
+            surface_config.format,
+            depth_texture.format
+        );
 
         Self {
             window_size,
+            cursor_position: None,
             camera,
 
             instance,
@@ -344,9 +356,10 @@ impl RenderState {
             queue,
 
             tile_quad_buffer,
-            shadow_quad_buffer,
             entity_quad_buffer,
             ui_quad_buffer,
+            debug_line_buffer,
+            grid_line_buffer,
 
             sprite_sheet,
             tile_sprite_sheet,
@@ -354,18 +367,36 @@ impl RenderState {
 
             depth_texture: Some(depth_texture),
 
+            render_scale: 1.0,
+            scene_target: None,
+            capture_requested: false,
+            captured_frame: None,
+
             camera_buffer,
             camera_bind_group,
 
             ui_camera_buffer,
             ui_camera_bind_group,
 
-            //render_pipelines,
+            global_buffer,
+            global_bind_group,
+
             clear_color: [0.0, 0.0, 0.0],
-            tile_render_pipeline,
-            entity_render_pipeline,
-            shadow_render_pipeline,
-            ui_render_pipeline,
+            frame_budget,
+            highlight_hovered_tile: true,
+            shadow_budget: None,
+            tree_cull_margin: 1.0,
+            tree_lod_min_pixels: 8.0,
+            understory_y_range: 0.1,
+            ground_shade_strength: 0.35,
+            ground_uv_inset: true,
+            grid_line_width: None,
+            wind: (0.03, 0.0),
+            debug_ui_theme: DebugUiTheme::default(),
+
+            shaders,
+            bind_group_layouts,
+            pipelines,
         }
     }
 
@@ -375,20 +406,143 @@ impl RenderState {
 
             self.surface_config.width  = new_size.width;
             self.surface_config.height = new_size.height;
+
+            // Moving to a different monitor can change the preferred format (e.g. HDR), pipelines target the old one.
+            let preferred_format = self.surface.get_preferred_format(&self.adapter);
+            if let Some(new_format) = surface_format_change(&self.surface_config, preferred_format) {
+                debug!("Surface format changed {:?} -> {:?}: Rebuilding pipelines.", self.surface_config.format, new_format);
+                self.surface_config.format = new_format;
+                self.rebuild_render_pipelines();
+            }
             self.surface.configure(&self.device, &self.surface_config);
 
-            self.depth_texture = Some(
-                Texture::create_depth_texture(
-                    "render_state.depth_texture",
-                    &self.device,
-                    self.surface_config.width,
-                    self.surface_config.height
-                )
-            );
+            self.recreate_render_targets();
         }
     }
 
-    pub fn try_render(&mut self, game_state: &GameState, dbgt: &mut DebugTimers) -> Result<(), wgpu::SurfaceError> {
+    /// GPU name, vendor and backend, handy for bug reports.
+    pub fn adapter_info(&self) -> wgpu::AdapterInfo {
+        self.adapter.get_info()
+    }
+
+    pub fn surface_format(&self) -> wgpu::TextureFormat {
+        self.surface_config.format
+    }
+
+    pub fn present_mode(&self) -> wgpu::PresentMode {
+        self.surface_config.present_mode
+    }
+
+    /// Reconfigures the surface, e.g. to toggle vsync. Modes the surface doesn't support end up as Fifo, see RenderState::new.
+    pub fn set_present_mode(&mut self, present_mode: wgpu::PresentMode) {
+        if present_mode == self.surface_config.present_mode { return; }
+
+        debug!("Present mode {:?} -> {:?}", self.surface_config.present_mode, present_mode);
+        self.surface_config.present_mode = present_mode;
+        self.surface.configure(&self.device, &self.surface_config);
+    }
+
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale
+    }
+
+    /// Render the scene at a fraction of the window resolution and upscale it, 0.5 => a quarter of the pixels to fill.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        let scale = scale.clamp(0.1, 1.0);
+        if scale == self.render_scale { return; }
+
+        self.render_scale = scale;
+        self.recreate_render_targets();
+    }
+
+    /// Size of the texture the scene is rendered into, before being stretched over the surface.
+    fn render_target_size(&self) -> (u32, u32) {
+        scaled_target_size((self.surface_config.width, self.surface_config.height), self.render_scale)
+    }
+
+    fn recreate_render_targets(&mut self) {
+        let (width, height) = self.render_target_size();
+
+        self.depth_texture = Some(Texture::create_depth_texture("render_state.depth_texture", &self.device, width, height));
+
+        self.scene_target = if self.render_scale != 1.0 {
+            Some(self.create_scene_target(width, height))
+        } else {
+            None
+        };
+    }
+
+    fn create_scene_target(&self, width: u32, height: u32) -> SceneTarget {
+        let texture = Texture::create_render_target("render_state.scene_target", &self.device, width, height, self.surface_config.format);
+
+        let bind_group = self.device.create_bind_group(
+            &wgpu::BindGroupDescriptor {
+                label: Some("render_state.scene_target_bind_group"),
+                layout: &self.bind_group_layouts.blit,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&texture.view) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&texture.sampler) },
+                ],
+            }
+        );
+
+        SceneTarget { texture, bind_group }
+    }
+
+    /// Reads back the next frame try_render draws, collect it with take_captured_frame afterwards.
+    pub fn request_capture(&mut self) {
+        self.capture_requested = true;
+    }
+
+    /// The frame read back after the last request_capture, debug ui included. None until try_render has run.
+    pub fn take_captured_frame(&mut self) -> Option<image::RgbaImage> {
+        self.captured_frame.take()
+    }
+
+    /// Snap zoom to whole pixels per texel so pixel art stays crisp, at the cost of slightly stepped zooming.
+    pub fn set_pixel_perfect_zoom(&mut self, enabled: bool) {
+        self.camera.pixel_snap = enabled;
+    }
+
+    /// None when the cursor has left the window.
+    pub fn set_cursor_position(&mut self, position: Option<winit::dpi::PhysicalPosition<f64>>) {
+        self.cursor_position = position;
+    }
+
+    pub fn screen_to_world(&self, pos: winit::dpi::PhysicalPosition<f64>) -> cgmath::Point2<f32> {
+        let ((min_x, min_y), (max_x, max_y)) = self.camera.world_view_rect(self.window_size);
+
+        // Window coordinates run top to bottom, world coordinates bottom to top.
+        let u = pos.x as f32 / self.window_size.width as f32;
+        let v = 1.0 - (pos.y as f32 / self.window_size.height as f32);
+
+        cgmath::Point2::new(min_x + (u * (max_x - min_x)), min_y + (v * (max_y - min_y)))
+    }
+
+    /// Recompile one shader and rebuild the pipelines, on error the old shader and pipelines stay in use.
+    pub fn reload_shader(&mut self, which: ShaderId, src: &str) -> Result<()> {
+        validate_wgsl(src).map_err(|e| e.context(format!("{} failed to compile", which.path())))?;
+
+        let module = create_shader_module(&self.device, which.label(), src);
+        *self.shaders.get_mut(which) = module;
+        self.rebuild_render_pipelines();
+
+        Ok(())
+    }
+
+    fn rebuild_render_pipelines(&mut self) {
+        let depth_format = self.depth_texture.as_ref().unwrap().format;
+
+        self.pipelines = create_render_pipelines(
+            &self.device,
+            &self.shaders,
+            &self.bind_group_layouts,
+            self.surface_config.format,
+            depth_format
+        );
+    }
+
+    pub fn try_render(&mut self, game_state: &GameState, sim_time: Duration, dbgt: &mut DebugTimers) -> Result<(), wgpu::SurfaceError> {
         measure!(dbgt.long_avg_render_timer, {
             measure!(dbgt.avg_render_timer, {
                 let output = self.surface.get_current_texture()?;
@@ -402,13 +556,24 @@ impl RenderState {
 
                 // Take depth texture our of self to appease borrow check.
                 let depth_texture = self.depth_texture.take().unwrap();
+                let capture = std::mem::take(&mut self.capture_requested);
+                // The surface texture can't be copied from, a capture at full scale draws into a temporary target instead.
+                let temporary_target = capture && self.scene_target.is_none();
+                let scene_target = if temporary_target {
+                    let (width, height) = self.render_target_size();
+                    Some(self.create_scene_target(width, height))
+                } else {
+                    self.scene_target.take()
+                };
+                let scene_view = scene_target.as_ref().map_or(&output_view, |target| &target.texture.view);
 
-                let [r, g, b] = self.clear_color;
+                let ambient_light = game_state.ambient_light();
+                let [r, g, b] = self.clear_color.map(|c| c * ambient_light as f64);
                 let render_pass_descriptor = wgpu::RenderPassDescriptor {
                     label: Some("render_state -> render_pass"),
                     color_attachments: &[
                         wgpu::RenderPassColorAttachment {
-                            view: &output_view,
+                            view: scene_view,
                             resolve_target: None,
                             ops: wgpu::Operations {
                                 load: wgpu::LoadOp::Clear(wgpu::Color { r, g, b, a: 1.0 }),
@@ -428,9 +593,12 @@ impl RenderState {
 
                 // TODO: this should probably be automatic?
                 self.tile_quad_buffer.reset();
-                self.shadow_quad_buffer.reset();
                 self.entity_quad_buffer.reset();
                 self.ui_quad_buffer.reset();
+                self.debug_line_buffer.reset();
+                self.grid_line_buffer.reset();
+
+                self.camera.update(&game_state.camera, self.window_size);
 
                 measure!(dbgt.ground_render_timer, {
                     self.draw_ground(game_state);
@@ -439,49 +607,103 @@ impl RenderState {
                 self.draw_debug_grid(game_state);
 
                 measure!(dbgt.tree_render_timer, {
-                    self.draw_trees(game_state);
+                    self.draw_entities(game_state);
                 });
 
+                self.draw_debug_vectors(game_state);
+                self.draw_seed_dispersals(game_state);
+                self.draw_origin_axes(game_state);
+                self.draw_tree_slot_indices(game_state);
+                self.draw_hovered_tile(game_state);
+
                 self.draw_debug_graphs(game_state, dbgt);
 
+                self.tile_quad_buffer.reallocate_if_grown(&self.device);
+                self.entity_quad_buffer.reallocate_if_grown(&self.device);
+                self.ui_quad_buffer.reallocate_if_grown(&self.device);
+                self.debug_line_buffer.reallocate_if_grown(&self.device);
+                self.grid_line_buffer.reallocate_if_grown(&self.device);
+
                 let mut render_pass = encoder.begin_render_pass(&render_pass_descriptor);
 
-                self.camera.update(&game_state.camera, self.window_size);
                 self.queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(&[CameraUniform::from(self.camera)]));
+                self.queue.write_buffer(&self.global_buffer, 0, bytemuck::cast_slice(&[GlobalUniform::new(sim_time, self.wind, ambient_light)]));
 
                 self.queue.write_geometry_buffer(&mut self.tile_quad_buffer);
-                self.queue.write_geometry_buffer(&mut self.shadow_quad_buffer);
                 self.queue.write_geometry_buffer(&mut self.entity_quad_buffer);
                 self.queue.write_geometry_buffer(&mut self.ui_quad_buffer);
+                self.queue.write_geometry_buffer(&mut self.debug_line_buffer);
+                self.queue.write_geometry_buffer(&mut self.grid_line_buffer);
 
                 //Ground
-                render_pass.set_pipeline(&self.tile_render_pipeline);
+                render_pass.set_pipeline(&self.pipelines.tile_render_pipeline);
                 render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
                 render_pass.set_bind_group(1, &self.tile_sprite_sheet_bind_group, &[]);
+                render_pass.set_bind_group(2, &self.global_bind_group, &[]);
                 render_pass.draw_geometry_buffer(&self.tile_quad_buffer);
 
-                //Shadows
-                render_pass.set_pipeline(&self.shadow_render_pipeline);
+                //Grid lines, same vertices as the ui but placed in the world.
+                render_pass.set_pipeline(&self.pipelines.ui_render_pipeline);
                 render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
-                render_pass.draw_geometry_buffer(&self.shadow_quad_buffer);
+                render_pass.draw_geometry_buffer(&self.grid_line_buffer);
 
-                //Trees
-                render_pass.set_pipeline(&self.entity_render_pipeline);
+                //Trees and their shadows
+                render_pass.set_pipeline(&self.pipelines.entity_render_pipeline);
                 render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
                 render_pass.set_bind_group(1, &self.tile_sprite_sheet_bind_group, &[]);
+                render_pass.set_bind_group(2, &self.global_bind_group, &[]);
                 render_pass.draw_geometry_buffer(&self.entity_quad_buffer);
 
+                //Debug lines
+                render_pass.set_pipeline(&self.pipelines.debug_line_render_pipeline);
+                render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+                render_pass.draw_geometry_buffer(&self.debug_line_buffer);
+
                 let camera_uniform = CameraUniform::simple_canvas_ortho(self.window_size.width, self.window_size.height);
                 self.queue.write_buffer(&self.ui_camera_buffer, 0, bytemuck::cast_slice(&[camera_uniform]));
 
-                render_pass.set_pipeline(&self.ui_render_pipeline);
+                render_pass.set_pipeline(&self.pipelines.ui_render_pipeline);
                 render_pass.set_bind_group(0, &self.ui_camera_bind_group, &[]);
                 render_pass.draw_geometry_buffer(&self.ui_quad_buffer);
 
                 drop(render_pass);
+
+                if let Some(target) = scene_target.as_ref() {
+                    let mut blit_pass = encoder.begin_render_pass(
+                        &wgpu::RenderPassDescriptor {
+                            label: Some("render_state -> blit_pass"),
+                            color_attachments: &[
+                                wgpu::RenderPassColorAttachment {
+                                    view: &output_view,
+                                    resolve_target: None,
+                                    ops: wgpu::Operations {
+                                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                                        store: true
+                                    },
+                                }
+                            ],
+                            depth_stencil_attachment: None,
+                        }
+                    );
+
+                    blit_pass.set_pipeline(&self.pipelines.blit_render_pipeline);
+                    blit_pass.set_bind_group(0, &target.bind_group, &[]);
+                    blit_pass.draw(0..3, 0..1);
+                }
+
+                let frame_capture = scene_target.as_ref().filter(|_| capture).map(|target| {
+                    let (width, height) = self.render_target_size();
+                    FrameCapture::encode(&self.device, &mut encoder, &target.texture, width, height)
+                });
+
                 self.queue.submit(std::iter::once(encoder.finish()));
                 output.present();
 
+                if let Some(frame_capture) = frame_capture {
+                    self.captured_frame = frame_capture.read(&self.device);
+                    if self.captured_frame.is_none() { error!("Failed to read back captured frame."); }
+                }
+
                 if let TimerState::Ready(_) = dbgt.debug_log_timer.check() {
                     dbgt.debug_log_timer.reset();
                     debug!(
@@ -493,6 +715,9 @@ impl RenderState {
 
                 // Replace depth texture in self now that we're done with it.
                 self.depth_texture = Some(depth_texture);
+                if !temporary_target {
+                    self.scene_target = scene_target;
+                }
             });
         });
 
@@ -501,45 +726,50 @@ impl RenderState {
     }
 
     fn draw_ground(&mut self, game_state: &GameState) {
-        use game_state::GRID_DIM;
         use game_state::TILE_DIM;
         use game_state::TILE_RAD;
 
-        const GRID_DIM_I32: i32 = GRID_DIM as i32;
-        const MAX_XY: i32 = GRID_DIM_I32 - 1;
+        let grid_dim = game_state.grid_dim();
+        let grid_dim_i32 = grid_dim as i32;
+        let max_xy = grid_dim_i32 - 1;
+
+        let (inset_u, inset_v) = if self.ground_uv_inset { self.sprite_sheet.half_texel_uv() } else { (0.0, 0.0) };
 
         //NOTE:
         //  Because we're rendering the _dual of the grid_, we're (over/under)-iterating and then
         //  clamping to generate dual nodes for grid cells at the edge (i.e cells without neighbors on all sides).
-        for tile_x in -1..=MAX_XY {
-            for tile_y in -1..=MAX_XY {
+        //  The hide_apron debug view skips those, leaving only dual cells built entirely from real grid cells.
+        let dual_xy = dual_cell_range(grid_dim, game_state.debug.hide_apron);
+
+        for tile_x in dual_xy.clone() {
+            for tile_y in dual_xy.clone() {
 
                 let bl_index = {
-                    let x = tile_x.clamp(0, MAX_XY);
-                    let y = tile_y.clamp(0, MAX_XY);
-                    ((y * GRID_DIM_I32) + x) as usize
+                    let x = tile_x.clamp(0, max_xy);
+                    let y = tile_y.clamp(0, max_xy);
+                    ((y * grid_dim_i32) + x) as usize
                 };
 
                 let br_index = {
-                    let x = (tile_x + 1).clamp(0, MAX_XY);
-                    let y =  tile_y     .clamp(0, MAX_XY);
-                    ((y * GRID_DIM_I32) + x) as usize
+                    let x = (tile_x + 1).clamp(0, max_xy);
+                    let y =  tile_y     .clamp(0, max_xy);
+                    ((y * grid_dim_i32) + x) as usize
                 };
 
                 let tl_index = {
-                    let x =  tile_x     .clamp(0, MAX_XY);
-                    let y = (tile_y + 1).clamp(0, MAX_XY);
-                    ((y * GRID_DIM_I32) + x) as usize
+                    let x =  tile_x     .clamp(0, max_xy);
+                    let y = (tile_y + 1).clamp(0, max_xy);
+                    ((y * grid_dim_i32) + x) as usize
                 };
 
                 let tr_index = {
-                    let x = (tile_x + 1).clamp(0, MAX_XY);
-                    let y = (tile_y + 1).clamp(0, MAX_XY);
-                    ((y * GRID_DIM_I32) + x) as usize
+                    let x = (tile_x + 1).clamp(0, max_xy);
+                    let y = (tile_y + 1).clamp(0, max_xy);
+                    ((y * grid_dim_i32) + x) as usize
                 };
 
                 // SAFETY:
-                //  Indices are clamped [0, GRID_DIM-1]
+                //  Indices are clamped [0, grid_dim-1]
                 let (bl, br, tl, tr) = unsafe {
                     (
                         game_state.tiles.get(bl_index).unwrap(),
@@ -549,129 +779,52 @@ impl RenderState {
                     )
                 };
 
-                let grass_cover = {
-                    const GRASS_TILES: [Option<TileType>; 16] = [
-                        None,                           //0000
-                        Some(TileType::GrassBR),        //0001
-                        Some(TileType::GrassBL),        //0010
-                        Some(TileType::GrassB),         //0011
-                        Some(TileType::GrassTL),        //0100
-                        Some(TileType::GrassDiagDown),  //0101
-                        Some(TileType::GrassL),         //0110
-                        Some(TileType::GrassBTL),       //0111
-                        Some(TileType::GrassTR),        //1000
-                        Some(TileType::GrassR),         //1001
-                        Some(TileType::GrassDiagUp),    //1010
-                        Some(TileType::GrassBTR),       //1011
-                        Some(TileType::GrassT),         //1100
-                        Some(TileType::GrassTBR),       //1101
-                        Some(TileType::GrassTBL),       //1110
-                        Some(TileType::Grass),          //1111
-                    ];
-
-                    let mut i = 0;
-
-                    if let GroundCover::Grass = tr.0 { i |= 0b1000 }
-                    if let GroundCover::Grass = tl.0 { i |= 0b0100 }
-                    if let GroundCover::Grass = bl.0 { i |= 0b0010 }
-                    if let GroundCover::Grass = br.0 { i |= 0b0001 }
-
-                    // SAFETY:
-                    //  indices 0000 -> 1111 are saturated.
-                    unsafe { GRASS_TILES.get_unchecked(i) }
-                };
-
-                let stone_cover = {
-                    const STONE_TILES: [Option<TileType>; 16] = [
-                        None,                           //0000
-                        Some(TileType::StoneBR),        //0001
-                        Some(TileType::StoneBL),        //0010
-                        Some(TileType::StoneB),         //0011
-                        Some(TileType::StoneTL),        //0100
-                        Some(TileType::StoneDiagDown),  //0101
-                        Some(TileType::StoneL),         //0110
-                        Some(TileType::StoneBTL),       //0111
-                        Some(TileType::StoneTR),        //1000
-                        Some(TileType::StoneR),         //1001
-                        Some(TileType::StoneDiagUp),    //1010
-                        Some(TileType::StoneBTR),       //1011
-                        Some(TileType::StoneT),         //1100
-                        Some(TileType::StoneTBR),       //1101
-                        Some(TileType::StoneTBL),       //1110
-                        Some(TileType::Stone),          //1111
-                    ];
-
-                    let mut i = 0;
-
-                    if let SoilType::Stony = tr.1 { i |= 0b1000 }
-                    if let SoilType::Stony = tl.1 { i |= 0b0100 }
-                    if let SoilType::Stony = bl.1 { i |= 0b0010 }
-                    if let SoilType::Stony = br.1 { i |= 0b0001 }
-
-                    // SAFETY:
-                    //  indices 0000 -> 1111 are saturated.
-                    unsafe { STONE_TILES.get_unchecked(i) }
+                let grass_amts = [
+                    *game_state.tile_grass_amt.get(tr_index).unwrap(),
+                    *game_state.tile_grass_amt.get(tl_index).unwrap(),
+                    *game_state.tile_grass_amt.get(bl_index).unwrap(),
+                    *game_state.tile_grass_amt.get(br_index).unwrap(),
+                ];
+                let stony = [tr, tl, bl, br].map(|tile| tile.1 == SoilType::Stony);
+
+                let ground_tint = {
+                    let light = [tr_index, tl_index, bl_index, br_index].iter()
+                        .map(|&index| *game_state.tile_light_amt.get(index).unwrap())
+                        .sum::<f32>() / 4.0;
+
+                    let brightness = ground_shade_brightness(light, self.ground_shade_strength);
+                    (brightness, brightness, brightness)
                 };
 
                 //NOTE:
                 //  Around the edge we just render a half size apron, these are fake "tiles",
                 //  the grid cells here don't actually have neighbors. Clamping snaps the apron to the edge of the actual grid.
-                let x = (((tile_x as f32) * TILE_DIM) + TILE_RAD).clamp(0.0, (TILE_DIM * GRID_DIM as f32));
-                let y = (((tile_y as f32) * TILE_DIM) + TILE_RAD).clamp(0.0, (TILE_DIM * GRID_DIM as f32));
-
-                let mut min_u = 0.0;
-                let mut max_u = 1.0;
-                let mut min_v = 0.0;
-                let mut max_v = 1.0;
-
-                {
-                    let max = (TILE_DIM * GRID_DIM as f32);
+                let x = (((tile_x as f32) * TILE_DIM) + TILE_RAD).clamp(0.0, (TILE_DIM * grid_dim as f32));
+                let y = (((tile_y as f32) * TILE_DIM) + TILE_RAD).clamp(0.0, (TILE_DIM * grid_dim as f32));
 
-                    //Janky.
-                    if      (x - 0.0).abs() < f32::EPSILON { min_u = 0.5; }
-                    else if ((x + TILE_RAD) - max).abs() < f32::EPSILON { max_u = 0.5; }
-
-                    if      (y - 0.0).abs() < f32::EPSILON { min_v = 0.5; }
-                    else if ((y + TILE_RAD) - max).abs() < f32::EPSILON { max_v = 0.5; }
-                }
+                let ((min_u, min_v), (max_u, max_v)) = ground_cell_uvs((x, y), TILE_DIM * grid_dim as f32, (inset_u, inset_v));
 
                 let mut dim_x = TILE_DIM;
                 let mut dim_y = TILE_DIM;
 
-                if tile_x == -1 || tile_x == MAX_XY { dim_x *= 0.5; }
-                if tile_y == -1 || tile_y == MAX_XY { dim_y *= 0.5; }
-
-                if grass_cover.is_none() || grass_cover.unwrap() != TileType::Grass {
-                    let quad = TexturedUvQuad {
-                        pos: (x, y),
-                        dim: (dim_x, dim_y),
-                        uv_min: (min_u, min_v),
-                        uv_max: (max_u, max_v),
-                        tex_index: self.sprite_sheet.get_texture_index(TileType::Dirt) as i32,
-                    };
-
-                    self.tile_quad_buffer.push_quad(quad);
-                }
+                if tile_x == -1 || tile_x == max_xy { dim_x *= 0.5; }
+                if tile_y == -1 || tile_y == max_xy { dim_y *= 0.5; }
 
-                if let Some(cover_type) = grass_cover {
-                    let quad = TexturedUvQuad {
-                        pos: (x, y),
-                        dim: (dim_x, dim_y),
-                        uv_min: (min_u, min_v),
-                        uv_max: (max_u, max_v),
-                        tex_index: self.sprite_sheet.get_texture_index(*cover_type) as i32,
+                for layer in ground_cell_layers(grass_amts, stony).into_iter().flatten() {
+                    let tint = if layer.shaded {
+                        (ground_tint.0, ground_tint.1, ground_tint.2, layer.alpha)
+                    } else {
+                        (1.0, 1.0, 1.0, layer.alpha)
                     };
 
-                    self.tile_quad_buffer.push_quad(quad);
-                }
-
-                if let Some(cover_type) = stone_cover {
                     let quad = TexturedUvQuad {
-                        pos: (x, y),
+                        pos: (x, y, layer.z),
                         dim: (dim_x, dim_y),
                         uv_min: (min_u, min_v),
                         uv_max: (max_u, max_v),
-                        tex_index: self.sprite_sheet.get_texture_index(*cover_type) as i32,
+                        tex_index: self.sprite_sheet.get_texture_index(layer.tile) as i32,
+                        tint,
+                        sway: 0.0,
                     };
 
                     self.tile_quad_buffer.push_quad(quad);
@@ -680,97 +833,95 @@ impl RenderState {
         }
     }
 
-    fn draw_trees(&mut self, game_state: &GameState) {
-        use game_state::GRID_DIM;
+    /// Trees and their shadows as one stream, painted back to front. Each shadow goes down just before its tree, so it
+    /// darkens the trees behind but never the ones in front.
+    fn draw_entities(&mut self, game_state: &GameState) {
         use game_state::TILE_DIM;
-        use game_state::TILE_RAD;
+
+        let grid_dim = game_state.grid_dim();
 
         if !game_state.debug.show_trees { return; }
 
+        let ((view_min_x, view_min_y), (view_max_x, view_max_y)) = {
+            let ((min_x, min_y), (max_x, max_y)) = self.camera.world_view_rect(self.window_size);
+            let margin = self.tree_cull_margin;
+
+            ((min_x - margin, min_y - margin), (max_x + margin, max_y + margin))
+        };
+
+        let merge_trees_per_tile = pixels_per_tile(self.window_size.height, self.camera.y_axis_dim) < self.tree_lod_min_pixels;
+
         //TODO: Memory Arena
         let mut trees_to_render = Vec::with_capacity(game_state.count_trees);
+        let species_config = game_state.species_config();
 
-        for tile_index in 0..game_state::GRID_SIZE {
+        for tile_index in 0..game_state.grid_size() {
             // SAFETY:
-            //  tile index ranges from 0..GRID_SIZE
+            //  tile index ranges from 0..grid_size
             let tree_iter = unsafe { game_state.iter_trees_on_tile_unchecked(tile_index) };
 
-            for tree in tree_iter {
-                //NOTE: Super hacky, because sprites don't encode "semantic" origin point, we're cheesing it.
-                //      shadows for 1 px wide trees are offset by half the tile width PLUS half of one pixel (1/32 tiles).
-                let do_hacky_shadow_offset = match tree.stage {
-                    game::TreeGrowthStage::Sprout   |
-                    game::TreeGrowthStage::Seedling => true,
-                    _ => false,
-                };
-
-                let x = (tile_index % GRID_DIM) as f32 * TILE_DIM - (TILE_RAD) + (TILE_DIM * tree.position.offset.x);
-                let y = (tile_index / GRID_DIM) as f32 * TILE_DIM              + (TILE_DIM * tree.position.offset.y);
-                let z = y;
+            for tree in lod_trees(tree_iter, merge_trees_per_tile, species_config) {
+                let tree_x = (tile_index % grid_dim) as f32 * TILE_DIM + (TILE_DIM * tree.position.offset.x);
+                let tree_y = (tile_index / grid_dim) as f32 * TILE_DIM + (TILE_DIM * tree.position.offset.y);
 
-                let tex_index = self.sprite_sheet.get_texture_index(TileType::from(tree)) as i32;
-                let shadow_radius = tree.species.shadow_radius(tree.stage);
+                let sprite = TileType::from(tree);
+                let (x, y) = sprite_origin((tree_x, tree_y), self.sprite_sheet.get_anchor(sprite), tree.flipped);
+                let shadow_radius = species_config.shadow_radius(tree.species, tree.stage);
+                let z = z_band::understory_entity_z(tree_y, shadow_radius, self.understory_y_range, grid_dim as f32 * TILE_DIM);
 
-                trees_to_render.push((x, y, z, tex_index, shadow_radius, do_hacky_shadow_offset));
-            }
-        }
+                if !sprite_in_view((x, y), (view_min_x, view_min_y), (view_max_x, view_max_y)) {
+                    continue;
+                }
 
-        for &(x, y, _, _, shadow_rad, do_hacky_shadow_offset) in trees_to_render.iter() {
-            let dim_x = TILE_DIM * shadow_rad;
-            let dim_y = TILE_DIM * shadow_rad * 0.25 ;
+                let tex_index = self.sprite_sheet.get_texture_index(sprite) as i32;
 
-            let mut pos_x = x + ((1.0 - dim_x) * 0.5);
-            let pos_y = y - (dim_y * 0.5);
+                let sway = tree_sway(tree.stage);
+                let tint = if tree.burning { BURNING_TREE_TINT } else { (1.0, 1.0, 1.0, 1.0) };
 
-            if do_hacky_shadow_offset {
-                pos_x += (TILE_DIM / 32.0) / 2.0;
+                trees_to_render.push(TreeSprite {
+                    pos: (x, y),
+                    base: (tree_x, tree_y),
+                    z,
+                    tex_index,
+                    shadow_radius,
+                    flipped: tree.flipped,
+                    sway,
+                    tint,
+                });
             }
-
-            let quad = UntexturedQuad {
-                pos: (pos_x, pos_y),
-                dim: (dim_x, dim_y),
-            };
-
-            self.shadow_quad_buffer.push_quad(quad);
         }
 
-        for &(x, y, z, tex_index, ..) in trees_to_render.iter() {
-            let quad = TexturedQuad {
-                pos: (x, y, z),
-                dim: (TILE_DIM, TILE_DIM),
-                tex_index
-            };
-
+        for quad in entity_quads(&trees_to_render, self.shadow_budget.unwrap_or(usize::MAX)) {
             self.entity_quad_buffer.push_quad(quad);
         }
     }
 
     fn draw_debug_grid(&mut self, game_state: &GameState) {
-        use game_state::GRID_DIM;
         use game_state::TILE_DIM;
         use game_state::TILE_RAD;
 
-
-        const GRID_DIM_I32: i32 = GRID_DIM as i32;
-        const MAX_XY: i32 = GRID_DIM_I32 - 1;
+        let grid_dim = game_state.grid_dim();
+        let grid_dim_i32 = grid_dim as i32;
+        let max_xy = grid_dim_i32 - 1;
 
         if game_state.debug.show_dual {
             // render Dual Grid Lines
-            for tile_x in -1..=MAX_XY {
-                for tile_y in -1..=MAX_XY {
-                    let x = (((tile_x as f32) * TILE_DIM) + TILE_RAD).clamp(0.0, (TILE_DIM * GRID_DIM as f32));
-                    let y = (((tile_y as f32) * TILE_DIM) + TILE_RAD).clamp(0.0, (TILE_DIM * GRID_DIM as f32));
+            for tile_x in -1..=max_xy {
+                for tile_y in -1..=max_xy {
+                    let x = (((tile_x as f32) * TILE_DIM) + TILE_RAD).clamp(0.0, (TILE_DIM * grid_dim as f32));
+                    let y = (((tile_y as f32) * TILE_DIM) + TILE_RAD).clamp(0.0, (TILE_DIM * grid_dim as f32));
 
                     let mut dim_x = TILE_DIM;
                     let mut dim_y = TILE_DIM;
 
-                    if tile_x == -1 || tile_x == MAX_XY { dim_x *= 0.5; }
-                    if tile_y == -1 || tile_y == MAX_XY { dim_y *= 0.5; }
+                    if tile_x == -1 || tile_x == max_xy { dim_x *= 0.5; }
+                    if tile_y == -1 || tile_y == max_xy { dim_y *= 0.5; }
 
                     let quad = TexturedQuad {
-                        pos: (x, y, 0.0),
+                        pos: (x, y, GROUND_Z_GRID),
                         dim: (dim_x, dim_y),
                         tex_index: self.sprite_sheet.get_texture_index(TileType::GridLine) as i32,
+                        tint: (1.0, 1.0, 1.0, 1.0),
                     };
 
                     self.tile_quad_buffer.push_quad(quad);
@@ -779,24 +930,188 @@ impl RenderState {
         }
 
         if game_state.debug.show_grid {
-            // render Grid Lines
-            for tile_x in 0..(GRID_DIM) {
-                for tile_y in 0..(GRID_DIM) {
-                    let x = ((tile_x as f32) * TILE_DIM);
-                    let y = ((tile_y as f32) * TILE_DIM);
+            if let Some(width) = self.grid_line_width {
+                self.draw_grid_lines(grid_dim, width);
+            } else {
+                // render Grid Lines
+                for tile_x in 0..(grid_dim) {
+                    for tile_y in 0..(grid_dim) {
+                        let x = ((tile_x as f32) * TILE_DIM);
+                        let y = ((tile_y as f32) * TILE_DIM);
+
+                        let quad = TexturedQuad {
+                            pos: (x, y, GROUND_Z_GRID),
+                            dim: (TILE_DIM, TILE_DIM),
+                            tex_index: self.sprite_sheet.get_texture_index(TileType::GridLine) as i32,
+                            tint: (1.0, 1.0, 1.0, 1.0),
+                        };
+
+                        self.tile_quad_buffer.push_quad(quad);
+                    }
+                }
+            }
+        }
+    }
 
-                    let quad = TexturedQuad {
-                        pos: (x, y, 0.0),
-                        dim: (TILE_DIM, TILE_DIM),
-                        tex_index: self.sprite_sheet.get_texture_index(TileType::GridLine) as i32,
-                    };
+    /// One quad per grid line, width in pixels.
+    fn draw_grid_lines(&mut self, grid_dim: usize, width: f32) {
+        let pixels_per_unit = self.window_size.height as f32 / self.camera.y_axis_dim;
 
-                    self.tile_quad_buffer.push_quad(quad);
+        for quad in grid_line_quads(grid_dim, width, pixels_per_unit) {
+            self.grid_line_buffer.push_quad(quad);
+        }
+    }
+
+    pub fn draw_debug_line(&mut self, from: (f32, f32), to: (f32, f32), color: (f32, f32, f32, f32)) {
+        let color = [color.0, color.1, color.2, color.3];
+
+        self.debug_line_buffer.push_line(
+            ColoredVertex { position: [from.0, from.1, OVERLAY_Z], color, rect: [0.0; 4], corner_radius: 0.0 },
+            ColoredVertex { position: [to.0,   to.1,   OVERLAY_Z], color, rect: [0.0; 4], corner_radius: 0.0 },
+        );
+    }
+
+    /// Shaft from -> to, plus two head segments swept back from `to`.
+    pub fn draw_arrow(&mut self, from: (f32, f32), to: (f32, f32), color: (f32, f32, f32, f32)) {
+        for (start, end) in arrow_segments(from, to) {
+            self.draw_debug_line(start, end, color);
+        }
+    }
+
+    /// Parent -> seedling arrows for seeds planted in the last second, fading as they age.
+    fn draw_seed_dispersals(&mut self, game_state: &GameState) {
+        use game_state::TILE_DIM;
+
+        if !game_state.debug.show_seed_dispersal { return; }
+
+        let to_world = |pos: WorldPosition| (
+            (pos.coord.x as f32 + pos.offset.x) * TILE_DIM,
+            (pos.coord.y as f32 + pos.offset.y) * TILE_DIM,
+        );
+
+        for dispersal in game_state.seed_dispersals() {
+            let alpha = 0.6 * (1.0 - (dispersal.age_s / SeedDispersal::FADE_S)).clamp(0.0, 1.0);
+            self.draw_arrow(to_world(dispersal.from), to_world(dispersal.to), (1.0, 0.9, 0.3, alpha));
+        }
+    }
+
+    /// Marks world (0, 0) with a cross, +X in red and +Y in green.
+    fn draw_origin_axes(&mut self, game_state: &GameState) {
+        if !game_state.debug.show_origin { return; }
+
+        for line in origin_axes_lines() {
+            self.draw_debug_line(line.from, line.to, line.color);
+        }
+    }
+
+    fn draw_debug_vectors(&mut self, game_state: &GameState) {
+        use game_state::TILE_DIM;
+
+        if !game_state.debug.show_debug_vectors { return; }
+
+        // Point from the center of the screen back towards the center of the grid, handy when you get lost.
+        let from = (self.camera.position.x, self.camera.position.y);
+        let grid_center = (game_state.grid_dim() as f32 * TILE_DIM) * 0.5;
+
+        let dir_x = grid_center - from.0;
+        let dir_y = grid_center - from.1;
+        let length = (dir_x * dir_x + dir_y * dir_y).sqrt();
+
+        if length > f32::EPSILON {
+            let arrow_length = f32::min(length, self.camera.y_axis_dim * 0.25);
+            let to = (from.0 + (dir_x / length) * arrow_length, from.1 + (dir_y / length) * arrow_length);
+
+            self.draw_arrow(from, to, (1.0, 0.2, 0.2, 1.0));
+        }
+    }
+
+    /// Labels every visible tree with its slot index, the first tree on each tile in yellow. Gaps or odd numbering
+    /// within a tile point at broken packing.
+    fn draw_tree_slot_indices(&mut self, game_state: &GameState) {
+        use game_state::TILE_DIM;
+
+        let grid_dim = game_state.grid_dim();
+
+        if !game_state.debug.show_slot_indices { return; }
+
+        const LABEL_HEIGHT: f32 = TILE_DIM * 0.15;
+
+        let ((view_min_x, view_min_y), (view_max_x, view_max_y)) = self.camera.world_view_rect(self.window_size);
+
+        for tile_index in 0..game_state.grid_size() {
+            let tile_x = (tile_index % grid_dim) as f32 * TILE_DIM;
+            let tile_y = (tile_index / grid_dim) as f32 * TILE_DIM;
+
+            if tile_x + TILE_DIM < view_min_x || tile_x > view_max_x || tile_y + TILE_DIM < view_min_y || tile_y > view_max_y {
+                continue;
+            }
+
+            for label in slot_labels(game_state, tile_index) {
+                let color = if label.first_on_tile { (1.0, 1.0, 0.2, 1.0) } else { (1.0, 1.0, 1.0, 1.0) };
+
+                self.draw_debug_number(label.slot_index, (label.pos.0, label.pos.1 - LABEL_HEIGHT * 1.2), LABEL_HEIGHT, color);
+            }
+        }
+    }
+
+    /// Seven segment style digits built from debug lines, pos is the bottom left of the first digit.
+    pub fn draw_debug_number(&mut self, value: usize, pos: (f32, f32), height: f32, color: (f32, f32, f32, f32)) {
+        //NOTE:
+        //  Segment bits: 0 top, 1 top right, 2 bottom right, 3 bottom, 4 bottom left, 5 top left, 6 middle.
+        const DIGIT_SEGMENTS: [u8; 10] = [
+            0b0111111, 0b0000110, 0b1011011, 0b1001111, 0b1100110,
+            0b1101101, 0b1111101, 0b0000111, 0b1111111, 0b1101111,
+        ];
+
+        let width = height * 0.5;
+        let advance = width * 1.5;
+        let half = height * 0.5;
+
+        for (i, digit) in value.to_string().bytes().enumerate() {
+            let segments = DIGIT_SEGMENTS[(digit - b'0') as usize];
+
+            let x0 = pos.0 + (i as f32 * advance);
+            let x1 = x0 + width;
+            let (y0, y1, y2) = (pos.1, pos.1 + half, pos.1 + height);
+
+            let lines = [
+                ((x0, y2), (x1, y2)),
+                ((x1, y2), (x1, y1)),
+                ((x1, y1), (x1, y0)),
+                ((x0, y0), (x1, y0)),
+                ((x0, y1), (x0, y0)),
+                ((x0, y2), (x0, y1)),
+                ((x0, y1), (x1, y1)),
+            ];
+
+            for (bit, &(from, to)) in lines.iter().enumerate() {
+                if segments & (1 << bit) != 0 {
+                    self.draw_debug_line(from, to, color);
                 }
             }
         }
     }
 
+    fn draw_hovered_tile(&mut self, game_state: &GameState) {
+        if !self.highlight_hovered_tile { return; }
+
+        let cursor_world = match self.cursor_position {
+            Some(position) => self.screen_to_world(position),
+            None => return,
+        };
+
+        let (min, max) = match hovered_tile_bounds(game_state, cursor_world) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let color = (1.0, 0.9, 0.3, 1.0);
+
+        self.draw_debug_line((min.0, min.1), (max.0, min.1), color);
+        self.draw_debug_line((max.0, min.1), (max.0, max.1), color);
+        self.draw_debug_line((max.0, max.1), (min.0, max.1), color);
+        self.draw_debug_line((min.0, max.1), (min.0, min.1), color);
+    }
+
     fn draw_debug_graphs(&mut self, game_state: &GameState, dbgt: &DebugTimers) {
         const WIDGET_WIDTH: i32 = 240;
         const WIDGET_HEIGHT: i32 = 20;
@@ -804,6 +1119,7 @@ impl RenderState {
 
         let ui_start = self.window_size.height as i32;
         let mut baseline = ui_start;
+        let theme = self.debug_ui_theme;
 
         let mut quads = Vec::<[_; 4]>::new();
 
@@ -819,22 +1135,15 @@ impl RenderState {
             let quad = ColoredQuad {
                 pos: (5.0, y_pos as f32),
                 dim: (WIDGET_WIDTH as f32, WIDGET_HEIGHT as f32),
-                color: (0.5, 0.5, 0.5, 0.3),
+                color: theme.background_color,
+                corner_radius: theme.panel_corner_radius,
             };
             quads.push(quad.into());
 
             let vertex_usage_perc = meter.vertex_usage as f32 / meter.vertex_capacity as f32;
             let index_usage_perc = meter.index_usage as f32 / meter.index_capacity as f32;
 
-            let choose_color = |perc| {
-                match perc {
-                    x if x > 0.90 => (0.6, 0.1, 0.1, 0.7),
-                    x if x > 0.75 => (0.5, 0.5, 0.2, 0.7),
-                    _ => (1.0, 1.0, 1.0, 0.7),
-                }
-            };
-
-            let vertex_bar_color = choose_color(vertex_usage_perc);
+            let vertex_bar_color = theme.usage_color(vertex_usage_perc);
             let index_bar_color = {
                 let mut c = vertex_bar_color;
                 c.0 *= 0.8;
@@ -848,6 +1157,7 @@ impl RenderState {
                     pos: (5.0, y_pos as f32 + (WIDGET_HEIGHT / 2) as f32),
                     dim: (WIDGET_WIDTH as f32 * vertex_usage_perc, (WIDGET_HEIGHT / 2) as f32),
                     color: vertex_bar_color,
+                    corner_radius: 0.0,
                 }.into()
             );
 
@@ -856,6 +1166,7 @@ impl RenderState {
                     pos: (5.0, y_pos as f32),
                     dim: (WIDGET_WIDTH as f32 * index_usage_perc, (WIDGET_HEIGHT / 2) as f32),
                     color: index_bar_color,
+                    corner_radius: 0.0,
                 }.into()
             );
 
@@ -874,7 +1185,8 @@ impl RenderState {
                 ColoredQuad {
                     pos: (5.0, y_pos as f32),
                     dim: (WIDGET_WIDTH as f32, PLOT_HEIGHT as f32),
-                    color: (0.5, 0.5, 0.5, 0.3),
+                    color: theme.background_color,
+                    corner_radius: theme.panel_corner_radius,
                 }.into()
             );
 
@@ -906,10 +1218,10 @@ impl RenderState {
 
                     quads.push(
                         [
-                            ColoredVertex { position: [x_max, y_max_right, 0.0], color },
-                            ColoredVertex { position: [x_min, y_max_left,  0.0], color },
-                            ColoredVertex { position: [x_min, y_min_left,  0.0], color },
-                            ColoredVertex { position: [x_max, y_min_right, 0.0], color },
+                            ColoredVertex { position: [x_max, y_max_right, UI_Z], color, rect: [0.0; 4], corner_radius: 0.0 },
+                            ColoredVertex { position: [x_min, y_max_left,  UI_Z], color, rect: [0.0; 4], corner_radius: 0.0 },
+                            ColoredVertex { position: [x_min, y_min_left,  UI_Z], color, rect: [0.0; 4], corner_radius: 0.0 },
+                            ColoredVertex { position: [x_max, y_min_right, UI_Z], color, rect: [0.0; 4], corner_radius: 0.0 },
                         ]
                     );
 
@@ -921,7 +1233,7 @@ impl RenderState {
             new_baseline
         };
 
-        let frame_budget = 8333.0; //micros, janky, hardcoded.
+        let frame_budget = self.frame_budget.as_micros() as f32;
 
         let update_times = dbgt.long_avg_update_timer.measurements();
         let render_times = dbgt.long_avg_render_timer.measurements();
@@ -934,8 +1246,6 @@ impl RenderState {
         baseline = add_spacer(baseline);
         baseline = add_buffer_usage_meter(baseline, (&self.tile_quad_buffer).into(), &mut quads);
 
-        baseline = add_spacer(baseline);
-        baseline = add_buffer_usage_meter(baseline, (&self.shadow_quad_buffer).into(), &mut quads);
         baseline = add_spacer(baseline);
         baseline = add_buffer_usage_meter(baseline, (&self.entity_quad_buffer).into(), &mut quads);
 
@@ -952,3 +1262,980 @@ impl RenderState {
         }
     }
 }
+
+struct ShaderModules {
+    main_shader: wgpu::ShaderModule,
+    ui_shader: wgpu::ShaderModule,
+    blit_shader: wgpu::ShaderModule,
+}
+
+impl ShaderModules {
+    fn get_mut(&mut self, which: ShaderId) -> &mut wgpu::ShaderModule {
+        match which {
+            ShaderId::Main   => &mut self.main_shader,
+            ShaderId::Ui     => &mut self.ui_shader,
+        }
+    }
+}
+
+/// Shaders that can be swapped out at runtime with RenderState::reload_shader.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShaderId {
+    Main,
+    Ui,
+}
+
+impl ShaderId {
+    pub const ALL: [ShaderId; 2] = [
+        Self::Main,
+        Self::Ui,
+    ];
+
+    /// Relative to the crate root, where the baked in copy was included from.
+    pub fn path(&self) -> &'static str {
+        match self {
+            Self::Main   => "res/shaders/main_shader.wgsl",
+            Self::Ui     => "res/shaders/debug_ui_shader.wgsl",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Main   => "render_state -> main_shader",
+            Self::Ui     => "render_state -> ui_shader",
+        }
+    }
+}
+
+struct BindGroupLayouts {
+    camera: wgpu::BindGroupLayout,
+    tile_sprite_sheet: wgpu::BindGroupLayout,
+    global: wgpu::BindGroupLayout,
+    blit: wgpu::BindGroupLayout,
+}
+
+struct SceneTarget {
+    texture: Texture,
+    bind_group: wgpu::BindGroup,
+}
+
+struct RenderPipelines {
+    tile_render_pipeline: wgpu::RenderPipeline,
+    entity_render_pipeline: wgpu::RenderPipeline,
+    ui_render_pipeline: wgpu::RenderPipeline,
+    debug_line_render_pipeline: wgpu::RenderPipeline,
+    blit_render_pipeline: wgpu::RenderPipeline,
+}
+
+// NOTE: Pipelines are baked against the surface format, so these have to be rebuilt whenever that changes.
+fn create_render_pipelines(
+    device: &wgpu::Device,
+    shaders: &ShaderModules,
+    bind_group_layouts: &BindGroupLayouts,
+    color_format: wgpu::TextureFormat,
+    depth_format: wgpu::TextureFormat,
+) -> RenderPipelines {
+    let camera_bind_group_layout = &bind_group_layouts.camera;
+    let tile_sprite_sheet_bind_group_layout = &bind_group_layouts.tile_sprite_sheet;
+    let global_bind_group_layout = &bind_group_layouts.global;
+
+    let tile_render_pipeline = {
+        let label = "render_state.tile_render_pipeline";
+        let bind_group_layouts = [camera_bind_group_layout, tile_sprite_sheet_bind_group_layout, global_bind_group_layout];
+        let push_constant_ranges = [];
+        let buffer_layouts = [TexturedVertex::describe_buffer()];
+
+        let depth_stencil = wgpu::DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::LessEqual,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        };
+
+        create_render_pipeline(
+            device,
+            label,
+            &bind_group_layouts,
+            &push_constant_ranges,
+            &buffer_layouts,
+            &shaders.main_shader,
+            PipelineOutput {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                color_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                depth_stencil: Some(depth_stencil),
+            },
+        )
+    };
+
+    let entity_render_pipeline = {
+        let label = "render_state.etity_render_pipeline";
+        let bind_group_layouts = [camera_bind_group_layout, tile_sprite_sheet_bind_group_layout, global_bind_group_layout];
+        let push_constant_ranges = [];
+        let buffer_layouts = [TexturedVertex::describe_buffer()];
+
+        let depth_stencil = wgpu::DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: true,
+            depth_compare: wgpu::CompareFunction::LessEqual,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        };
+
+        create_render_pipeline(
+            device,
+            label,
+            &bind_group_layouts,
+            &push_constant_ranges,
+            &buffer_layouts,
+            &shaders.main_shader,
+            PipelineOutput {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                color_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                depth_stencil: Some(depth_stencil),
+            },
+        )
+    };
+
+    let ui_render_pipeline = {
+        let label = "render_state.debug_ui_render_pipeline";
+        let bind_group_layouts = [camera_bind_group_layout];
+        let push_constant_ranges = [];
+        let buffer_layouts = [ColoredVertex::describe_buffer()];
+
+        let depth_stencil = wgpu::DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        };
+
+        create_render_pipeline(
+            device,
+            label,
+            &bind_group_layouts,
+            &push_constant_ranges,
+            &buffer_layouts,
+            &shaders.ui_shader,
+            PipelineOutput {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                color_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                depth_stencil: Some(depth_stencil),
+            },
+        )
+    };
+
+    let debug_line_render_pipeline = {
+        let label = "render_state.debug_line_render_pipeline";
+        let bind_group_layouts = [camera_bind_group_layout];
+        let push_constant_ranges = [];
+        let buffer_layouts = [ColoredVertex::describe_buffer()];
+
+        let depth_stencil = wgpu::DepthStencilState {
+            format: depth_format,
+            depth_write_enabled: false,
+            depth_compare: wgpu::CompareFunction::Always,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        };
+
+        create_render_pipeline(
+            device,
+            label,
+            &bind_group_layouts,
+            &push_constant_ranges,
+            &buffer_layouts,
+            &shaders.ui_shader,
+            PipelineOutput {
+                topology: wgpu::PrimitiveTopology::LineList,
+                color_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                depth_stencil: Some(depth_stencil),
+            },
+        )
+    };
+
+    let blit_render_pipeline = {
+        let label = "render_state.blit_render_pipeline";
+        let bind_group_layouts = [&bind_group_layouts.blit];
+        let push_constant_ranges = [];
+        let buffer_layouts = [];
+
+        create_render_pipeline(
+            device,
+            label,
+            &bind_group_layouts,
+            &push_constant_ranges,
+            &buffer_layouts,
+            &shaders.blit_shader,
+            PipelineOutput {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                color_format,
+                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                depth_stencil: None,
+            },
+        )
+    };
+
+    RenderPipelines {
+        tile_render_pipeline,
+        entity_render_pipeline,
+        ui_render_pipeline,
+        debug_line_render_pipeline,
+        blit_render_pipeline,
+    }
+}
+
+/// World space (min, max) corners of the tile under cursor_world, None off the grid.
+fn hovered_tile_bounds(game_state: &GameState, cursor_world: cgmath::Point2<f32>) -> Option<((f32, f32), (f32, f32))> {
+    use game_state::TILE_DIM;
+
+    let tile = game_state.world_to_tile(cursor_world)?;
+
+    let min = (tile.x as f32 * TILE_DIM, tile.y as f32 * TILE_DIM);
+    let max = (min.0 + TILE_DIM, min.1 + TILE_DIM);
+    Some((min, max))
+}
+
+struct SlotLabel {
+    slot_index: usize,
+    // World space position of the tree being labelled.
+    pos: (f32, f32),
+    first_on_tile: bool,
+}
+
+/// Slot index labels for the trees on tile_index, in slot order.
+fn slot_labels(game_state: &GameState, tile_index: usize) -> Vec<SlotLabel> {
+    use game_state::{NUM_TREES_PER_TILE, TILE_DIM, tree_slot_index};
+
+    let grid_dim = game_state.grid_dim();
+    let tile_x = (tile_index % grid_dim) as f32 * TILE_DIM;
+    let tile_y = (tile_index / grid_dim) as f32 * TILE_DIM;
+
+    // SAFETY:
+    //  callers pass tile indices ranging from 0..grid_size
+    let trees = unsafe { game_state.iter_trees_on_tile_unchecked(tile_index) };
+
+    trees.enumerate()
+        .map(|(tree_index, tree)| SlotLabel {
+            slot_index: tree_slot_index!(tile_index, tree_index),
+            pos: (tile_x + (TILE_DIM * tree.position.offset.x), tile_y + (TILE_DIM * tree.position.offset.y)),
+            first_on_tile: tree_index == 0,
+        })
+        .collect()
+}
+
+/// Dual cell coordinates along each axis, each cell sits on the corner shared by tiles (x, y) and (x + 1, y + 1).
+/// Without the apron only cells built from four real tiles are kept.
+fn dual_cell_range(grid_dim: usize, hide_apron: bool) -> std::ops::RangeInclusive<i32> {
+    let max_xy = grid_dim as i32 - 1;
+
+    if hide_apron { 0..=(max_xy - 1) } else { -1..=max_xy }
+}
+
+/// (min, max) uvs for the dual cell quad centered at pos, apron cells on the grid's edge only show the half of the
+/// sprite inside the grid. The uvs are pulled in by inset so filtering never samples past the sprite.
+fn ground_cell_uvs(pos: (f32, f32), grid_extent: f32, inset: (f32, f32)) -> ((f32, f32), (f32, f32)) {
+    use game_state::TILE_RAD;
+
+    let (x, y) = pos;
+    let mut min_u = 0.0;
+    let mut max_u = 1.0;
+    let mut min_v = 0.0;
+    let mut max_v = 1.0;
+
+    //Janky.
+    if      (x - 0.0).abs() < f32::EPSILON { min_u = 0.5; }
+    else if ((x + TILE_RAD) - grid_extent).abs() < f32::EPSILON { max_u = 0.5; }
+
+    if      (y - 0.0).abs() < f32::EPSILON { min_v = 0.5; }
+    else if ((y + TILE_RAD) - grid_extent).abs() < f32::EPSILON { max_v = 0.5; }
+
+    (
+        (f32::max(min_u, inset.0), f32::max(min_v, inset.1)),
+        (f32::min(max_u, 1.0 - inset.0), f32::min(max_v, 1.0 - inset.1)),
+    )
+}
+
+/// One ground sprite in a dual cell, shaded layers take the cell's ground tint.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct GroundLayer {
+    tile: TileType,
+    z: f32,
+    alpha: f32,
+    shaded: bool,
+}
+
+/// Ground sprites for a dual cell in push order: dirt unless fully grassed, solid grass, fading grass, then stone.
+/// Corners are ordered (tr, tl, bl, br).
+fn ground_cell_layers(grass_amts: [f32; 4], stony: [bool; 4]) -> [Option<GroundLayer>; 4] {
+    //NOTE:
+    //  Corners which are fully grassed make up the solid grass layer, corners mid transition get
+    //  an extra layer (solid + transitioning corners) faded by their average grass amount.
+    let (grass_cover, fading_grass_cover) = {
+        const GRASS_TILES: [Option<TileType>; 16] = [
+            None,                           //0000
+            Some(TileType::GrassBR),        //0001
+            Some(TileType::GrassBL),        //0010
+            Some(TileType::GrassB),         //0011
+            Some(TileType::GrassTL),        //0100
+            Some(TileType::GrassDiagDown),  //0101
+            Some(TileType::GrassL),         //0110
+            Some(TileType::GrassBTL),       //0111
+            Some(TileType::GrassTR),        //1000
+            Some(TileType::GrassR),         //1001
+            Some(TileType::GrassDiagUp),    //1010
+            Some(TileType::GrassBTR),       //1011
+            Some(TileType::GrassT),         //1100
+            Some(TileType::GrassTBR),       //1101
+            Some(TileType::GrassTBL),       //1110
+            Some(TileType::Grass),          //1111
+        ];
+
+        let mut solid_i = 0;
+        let mut fading_i = 0;
+        let mut fading_amt = 0.0;
+        let mut fading_count = 0;
+
+        for (bit, &amt) in [0b1000, 0b0100, 0b0010, 0b0001].iter().zip(grass_amts.iter()) {
+            if amt >= 1.0 {
+                solid_i |= bit;
+            } else if amt > 0.0 {
+                fading_i |= bit;
+                fading_amt += amt;
+                fading_count += 1;
+            }
+        }
+
+        // SAFETY:
+        //  indices 0000 -> 1111 are saturated.
+        let solid = unsafe { GRASS_TILES.get_unchecked(solid_i) };
+        let fading = if fading_i != 0 {
+            // SAFETY:
+            //  indices 0000 -> 1111 are saturated.
+            let fading_cover = unsafe { GRASS_TILES.get_unchecked(solid_i | fading_i) };
+            fading_cover.map(|cover_type| (cover_type, fading_amt / fading_count as f32))
+        } else {
+            None
+        };
+
+        (solid, fading)
+    };
+
+    let stone_cover = {
+        const STONE_TILES: [Option<TileType>; 16] = [
+            None,                           //0000
+            Some(TileType::StoneBR),        //0001
+            Some(TileType::StoneBL),        //0010
+            Some(TileType::StoneB),         //0011
+            Some(TileType::StoneTL),        //0100
+            Some(TileType::StoneDiagDown),  //0101
+            Some(TileType::StoneL),         //0110
+            Some(TileType::StoneBTL),       //0111
+            Some(TileType::StoneTR),        //1000
+            Some(TileType::StoneR),         //1001
+            Some(TileType::StoneDiagUp),    //1010
+            Some(TileType::StoneBTR),       //1011
+            Some(TileType::StoneT),         //1100
+            Some(TileType::StoneTBR),       //1101
+            Some(TileType::StoneTBL),       //1110
+            Some(TileType::Stone),          //1111
+        ];
+
+        let mut i = 0;
+
+        for (bit, &is_stony) in [0b1000, 0b0100, 0b0010, 0b0001].iter().zip(stony.iter()) {
+            if is_stony { i |= bit }
+        }
+
+        // SAFETY:
+        //  indices 0000 -> 1111 are saturated.
+        unsafe { STONE_TILES.get_unchecked(i) }
+    };
+
+    let dirt = match grass_cover {
+        Some(TileType::Grass) => None,
+        _ => Some(GroundLayer { tile: TileType::Dirt, z: GROUND_Z_DIRT, alpha: 1.0, shaded: true }),
+    };
+
+    [
+        dirt,
+        grass_cover.map(|tile| GroundLayer { tile, z: GROUND_Z_GRASS, alpha: 1.0, shaded: true }),
+        fading_grass_cover.map(|(tile, fade)| GroundLayer { tile, z: GROUND_Z_GRASS, alpha: fade, shaded: true }),
+        stone_cover.map(|tile| GroundLayer { tile, z: GROUND_Z_STONE, alpha: 1.0, shaded: false }),
+    ]
+}
+
+/// The trees on a tile worth drawing. Zoomed far out (merge_trees_per_tile) each tile is represented by its biggest tree.
+fn lod_trees<'t, I>(mut trees: I, merge_trees_per_tile: bool, species_config: &SpeciesConfig) -> std::iter::Chain<std::option::IntoIter<&'t Tree>, I>
+where
+    I: Iterator<Item=&'t Tree>
+{
+    let lod_tree = if merge_trees_per_tile {
+        trees.by_ref().max_by(|a, b| {
+            let a_rad = species_config.shadow_radius(a.species, a.stage);
+            let b_rad = species_config.shadow_radius(b.species, b.stage);
+            a_rad.partial_cmp(&b_rad).unwrap()
+        })
+    } else {
+        None
+    };
+
+    // Merging drains trees, so only the biggest is left.
+    lod_tree.into_iter().chain(trees)
+}
+
+/// Whether a tree sprite with its bottom left at pos could show inside the view rect. Sprites are a tile wide and
+/// sit on top of their base, shadows hang below it.
+fn sprite_in_view(pos: (f32, f32), view_min: (f32, f32), view_max: (f32, f32)) -> bool {
+    use game_state::TILE_DIM;
+
+    let (x, y) = pos;
+    !(x + TILE_DIM < view_min.0 || x > view_max.0 || y + TILE_DIM < view_min.1 || y - TILE_DIM > view_max.1)
+}
+
+/// A tree placed and layered for drawing, see draw_entities.
+#[derive(Clone, Copy, Debug)]
+struct TreeSprite {
+    // Bottom left corner of the sprite.
+    pos: (f32, f32),
+    // The tree's position, where the sprite's anchor lands.
+    base: (f32, f32),
+    z: f32,
+    tex_index: i32,
+    shadow_radius: f32,
+    flipped: bool,
+    sway: f32,
+    tint: (f32, f32, f32, f32),
+}
+
+/// Bottom left corner for a sprite whose anchor should land on base. Flipped sprites mirror their anchor too.
+fn sprite_origin(base: (f32, f32), anchor: (f32, f32), flipped: bool) -> (f32, f32) {
+    use game_state::TILE_DIM;
+
+    let anchor_x = if flipped { 1.0 - anchor.0 } else { anchor.0 };
+    (base.0 - (TILE_DIM * anchor_x), base.1 - (TILE_DIM * anchor.1))
+}
+
+/// How far a tree's crown follows the wind, 1.0 for a full canopy. Dead wood has no canopy to catch it.
+fn tree_sway(stage: TreeGrowthStage) -> f32 {
+    match stage {
+        TreeGrowthStage::Snag  => 0.25,
+        TreeGrowthStage::Stump => 0.0,
+        _ => 1.0,
+    }
+}
+
+/// Tree and shadow quads in the order they're painted, back to front. Only the biggest shadows that fit
+/// shadow_budget are kept, each one going down just before its own tree.
+fn entity_quads(trees: &[TreeSprite], shadow_budget: usize) -> Vec<TexturedUvQuad> {
+    use game_state::TILE_DIM;
+
+    let mut shadow_cutoff = ShadowCutoff::new(trees.iter().map(|t| t.shadow_radius), shadow_budget);
+
+    let mut entity_quads = Vec::with_capacity(trees.len() * 2);
+    for tree in trees.iter() {
+        let z = tree.z;
+
+        if shadow_cutoff.admit(tree.shadow_radius) {
+            let dim_x = TILE_DIM * tree.shadow_radius;
+            let dim_y = TILE_DIM * tree.shadow_radius * 0.25 ;
+
+            // Shadows are centered on the tree's anchor point, at the tree's depth.
+            entity_quads.push((z, TexturedUvQuad {
+                pos: (tree.base.0 - (dim_x * 0.5), tree.base.1 - (dim_y * 0.5), z),
+                dim: (dim_x, dim_y),
+                uv_min: (0.0, 0.0),
+                uv_max: (1.0, 1.0),
+                tex_index: SHADOW_TEX_INDEX,
+                tint: (1.0, 1.0, 1.0, 1.0),
+                sway: 0.0,
+            }));
+        }
+
+        // Mirroring is just swapping which edge of the quad gets which u.
+        let (min_u, max_u) = if tree.flipped { (1.0, 0.0) } else { (0.0, 1.0) };
+
+        entity_quads.push((z, TexturedUvQuad {
+            pos: (tree.pos.0, tree.pos.1, z),
+            dim: (TILE_DIM, TILE_DIM),
+            uv_min: (min_u, 0.0),
+            uv_max: (max_u, 1.0),
+            tex_index: tree.tex_index,
+            tint: tree.tint,
+            sway: tree.sway,
+        }));
+    }
+
+    // Farthest (largest z) first. The sort is stable, a shadow stays ahead of its own tree.
+    entity_quads.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    entity_quads.into_iter().map(|(_, quad)| quad).collect()
+}
+
+/// Vertical and horizontal line quads for every grid line, width pixels thick at the given zoom.
+fn grid_line_quads(grid_dim: usize, width: f32, pixels_per_unit: f32) -> Vec<LineQuad> {
+    use game_state::TILE_DIM;
+
+    let world_width = width / pixels_per_unit;
+    let grid_extent = grid_dim as f32 * TILE_DIM;
+    let color = (0.0, 0.0, 0.0, 0.5);
+
+    let mut quads = Vec::with_capacity((grid_dim + 1) * 2);
+    for i in 0..=grid_dim {
+        let offset = (i as f32 * TILE_DIM) - (world_width * 0.5);
+
+        // Vertical then horizontal, each reaching half a line past the grid so the corners are filled.
+        quads.push(LineQuad {
+            pos: (offset, -world_width * 0.5, GROUND_Z_GRID),
+            dim: (world_width, grid_extent + world_width),
+            color,
+            pixels_per_unit,
+        });
+        quads.push(LineQuad {
+            pos: (-world_width * 0.5, offset, GROUND_Z_GRID),
+            dim: (grid_extent + world_width, world_width),
+            color,
+            pixels_per_unit,
+        });
+    }
+
+    quads
+}
+
+/// On screen height of a tile, for a window window_height pixels tall showing y_axis_dim world units.
+fn pixels_per_tile(window_height: u32, y_axis_dim: f32) -> f32 {
+    (window_height as f32 / y_axis_dim) * game_state::TILE_DIM
+}
+
+/// Shaft first, then the two head segments, each running out from `to`.
+fn arrow_segments(from: (f32, f32), to: (f32, f32)) -> [((f32, f32), (f32, f32)); 3] {
+    const HEAD_ANGLE: f32 = 0.5; // radians off the shaft.
+    const HEAD_LENGTH: f32 = 0.2; // fraction of the shaft length.
+
+    let back_x = (from.0 - to.0) * HEAD_LENGTH;
+    let back_y = (from.1 - to.1) * HEAD_LENGTH;
+
+    let head = |angle: f32| {
+        let (sin, cos) = angle.sin_cos();
+        (to.0 + (back_x * cos) - (back_y * sin), to.1 + (back_x * sin) + (back_y * cos))
+    };
+
+    [(from, to), (to, head(HEAD_ANGLE)), (to, head(-HEAD_ANGLE))]
+}
+
+struct DebugLine {
+    from: (f32, f32),
+    to: (f32, f32),
+    color: (f32, f32, f32, f32),
+}
+
+/// The two strokes of the origin cross, then the +X arrow's segments and the +Y arrow's.
+fn origin_axes_lines() -> Vec<DebugLine> {
+    use game_state::TILE_DIM;
+
+    let marker_rad = TILE_DIM * 0.1;
+    let axis_length = TILE_DIM * 2.0;
+    let marker_color = (1.0, 1.0, 1.0, 1.0);
+    let x_color = (1.0, 0.2, 0.2, 1.0);
+    let y_color = (0.2, 1.0, 0.2, 1.0);
+
+    let mut lines = vec![
+        DebugLine { from: (-marker_rad, -marker_rad), to: (marker_rad, marker_rad), color: marker_color },
+        DebugLine { from: (-marker_rad, marker_rad), to: (marker_rad, -marker_rad), color: marker_color },
+    ];
+    lines.extend(arrow_segments((0.0, 0.0), (axis_length, 0.0)).map(|(from, to)| DebugLine { from, to, color: x_color }));
+    lines.extend(arrow_segments((0.0, 0.0), (0.0, axis_length)).map(|(from, to)| DebugLine { from, to, color: y_color }));
+    lines
+}
+
+/// window_size * render_scale, never collapsing to zero.
+fn scaled_target_size(window_size: (u32, u32), render_scale: f32) -> (u32, u32) {
+    let width  = (window_size.0 as f32 * render_scale).round() as u32;
+    let height = (window_size.1 as f32 * render_scale).round() as u32;
+
+    (width.max(1), height.max(1))
+}
+
+/// What RenderState::new configures the surface with, and what the surface_format / present_mode accessors report
+/// until something changes it.
+fn initial_surface_config(format: wgpu::TextureFormat, window_size: winit::dpi::PhysicalSize<u32>) -> wgpu::SurfaceConfiguration {
+    wgpu::SurfaceConfiguration {
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        format,
+        width: window_size.width,
+        height: window_size.height,
+
+        // present_mode:
+        //  Fifo      => VScync.
+        //  Mailbox   => Submit eagerly, VScync, fallback to Fifo if unsupported.
+        //  Immediate => Low latency, no wait for display, tearing may be observed.
+        // NOTE: wgpu 0.11 can't list the modes a surface supports, configure falls back to Fifo (with a warning) itself.
+        present_mode: wgpu::PresentMode::Mailbox,
+    }
+}
+
+/// The new format to switch to, if the surface's preferred format no longer matches the configured one.
+fn surface_format_change(config: &wgpu::SurfaceConfiguration, preferred_format: Option<wgpu::TextureFormat>) -> Option<wgpu::TextureFormat> {
+    match preferred_format {
+        Some(format) if format != config.format => Some(format),
+        _ => None,
+    }
+}
+
+/// Ground color multiplier for the given light (0.0 shaded -> 1.0 lit), strength is how dark full shade gets.
+fn ground_shade_brightness(light: f32, strength: f32) -> f32 {
+    1.0 - (strength.clamp(0.0, 1.0) * (1.0 - light.clamp(0.0, 1.0)))
+}
+
+/// Which shadows fit when only `budget` of them can be drawn, the biggest shadows win. Radius only depends on species
+/// and stage so ties at the cutoff are common, only as many of those as the budget has room for are admitted.
+struct ShadowCutoff {
+    min_radius: f32,
+    ties_left: usize,
+}
+
+impl ShadowCutoff {
+    fn new(shadow_radii: impl Iterator<Item=f32>, budget: usize) -> Self {
+        let mut radii = shadow_radii.filter(|&r| r > 0.0).collect::<Vec<_>>();
+
+        if radii.len() <= budget { return Self { min_radius: 0.0, ties_left: usize::MAX }; }
+        if budget == 0 { return Self { min_radius: f32::INFINITY, ties_left: 0 }; }
+
+        radii.sort_by(|a, b| b.partial_cmp(a).unwrap());
+        let min_radius = radii[budget - 1];
+        let above_min = radii.iter().take_while(|&&r| r > min_radius).count();
+
+        Self { min_radius, ties_left: budget - above_min }
+    }
+
+    /// Whether a shadow of this radius gets drawn, call once per shadow.
+    fn admit(&mut self, radius: f32) -> bool {
+        if radius <= 0.0 || radius < self.min_radius { return false; }
+        if radius > self.min_radius { return true; }
+        if self.ties_left == 0 { return false; }
+
+        self.ties_left -= 1;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::{TileCoordinate, TileOffset, TreeSpecies};
+
+    fn admitted(radii: &[f32], budget: usize) -> Vec<f32> {
+        let mut cutoff = ShadowCutoff::new(radii.iter().copied(), budget);
+        radii.iter().copied().filter(|&r| cutoff.admit(r)).collect()
+    }
+
+    fn tree_sprite(base: (f32, f32), z: f32, shadow_radius: f32) -> TreeSprite {
+        TreeSprite {
+            pos: sprite_origin(base, (0.5, 0.0), false),
+            base,
+            z,
+            tex_index: 3,
+            shadow_radius,
+            flipped: false,
+            sway: 1.0,
+            tint: (1.0, 1.0, 1.0, 1.0),
+        }
+    }
+
+    #[test]
+    fn sprites_and_shadows_land_on_the_anchor() {
+        let base = (4.0, 2.0);
+        let anchor = (16.5 / 32.0, 0.25);
+
+        let (x, y) = sprite_origin(base, anchor, false);
+        assert!((x - (4.0 - 16.5 / 32.0)).abs() < 1e-6);
+        assert!((y - 1.75).abs() < 1e-6);
+
+        // Mirrored, the anchor is the same distance in from the other edge.
+        let (flipped_x, _) = sprite_origin(base, anchor, true);
+        assert!((flipped_x - (4.0 - 15.5 / 32.0)).abs() < 1e-6);
+
+        let quads = entity_quads(&[tree_sprite(base, 50.0, 2.0)], usize::MAX);
+        let [shadow, tree] = &quads[..] else { panic!("expected a shadow and a tree") };
+
+        assert_eq!(shadow.tex_index, SHADOW_TEX_INDEX);
+        assert_eq!((shadow.pos.0 + shadow.dim.0 * 0.5, shadow.pos.1 + shadow.dim.1 * 0.5), base);
+        assert_eq!((tree.pos.0 + tree.dim.0 * 0.5, tree.pos.1), base);
+    }
+
+    #[test]
+    fn surface_format_changes_are_detected() {
+        let config = wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Bgra8UnormSrgb,
+            width: 800,
+            height: 600,
+            present_mode: wgpu::PresentMode::Fifo,
+        };
+
+        assert_eq!(surface_format_change(&config, Some(wgpu::TextureFormat::Rgba16Float)), Some(wgpu::TextureFormat::Rgba16Float));
+        assert_eq!(surface_format_change(&config, Some(wgpu::TextureFormat::Bgra8UnormSrgb)), None);
+        assert_eq!(surface_format_change(&config, None), None);
+    }
+
+    #[test]
+    fn hovered_tile_outline_covers_the_tile_under_the_cursor() {
+        let state = GameState::new_seeded_with_dimensions(1, 10);
+
+        assert_eq!(hovered_tile_bounds(&state, cgmath::Point2::new(3.4, 7.9)), Some(((3.0, 7.0), (4.0, 8.0))));
+        assert_eq!(hovered_tile_bounds(&state, cgmath::Point2::new(-0.2, 5.0)), None);
+        assert_eq!(hovered_tile_bounds(&state, cgmath::Point2::new(5.0, 10.5)), None);
+    }
+
+    #[test]
+    fn hiding_the_apron_drops_the_edge_dual_cells() {
+        let grid_dim = 30;
+
+        assert_eq!(dual_cell_range(grid_dim, false).count().pow(2), (grid_dim + 1) * (grid_dim + 1));
+        assert_eq!(dual_cell_range(grid_dim, true).count().pow(2), (grid_dim - 1) * (grid_dim - 1));
+        assert_eq!(dual_cell_range(grid_dim, true), 0..=28);
+    }
+
+    #[test]
+    fn off_screen_trees_are_culled() {
+        let (view_min, view_max) = ((0.0, 0.0), (10.0, 6.0));
+
+        assert!(sprite_in_view((4.0, 3.0), view_min, view_max));
+        // Poking in from the bottom left, and a shadow hanging into the top.
+        assert!(sprite_in_view((-0.5, -0.5), view_min, view_max));
+        assert!(sprite_in_view((4.0, 6.5), view_min, view_max));
+
+        assert!(!sprite_in_view((-1.5, 3.0), view_min, view_max));
+        assert!(!sprite_in_view((10.5, 3.0), view_min, view_max));
+        assert!(!sprite_in_view((4.0, 7.5), view_min, view_max));
+    }
+
+    #[test]
+    fn zoomed_out_tiles_draw_only_their_biggest_tree() {
+        let config = SpeciesConfig::default();
+        let position = WorldPosition { coord: TileCoordinate { x: 0, y: 0 }, offset: TileOffset { x: 0.5, y: 0.5 } };
+        let trees = [TreeGrowthStage::Sapling, TreeGrowthStage::Old, TreeGrowthStage::Seedling]
+            .map(|stage| Tree::builder(TreeSpecies::Ash, position).stage(stage).build(&config));
+
+        assert_eq!(lod_trees(trees.iter(), false, &config).count(), 3);
+
+        let merged = lod_trees(trees.iter(), true, &config).map(|tree| tree.stage).collect::<Vec<_>>();
+        assert_eq!(merged, [TreeGrowthStage::Old]);
+    }
+
+    #[test]
+    fn tree_lod_kicks_in_zoomed_out_on_big_grids() {
+        let lod_min_pixels = 8.0;
+
+        let small = GameState::new_seeded_with_dimensions(1, 30);
+        assert!(pixels_per_tile(720, small.max_zoom()) >= lod_min_pixels);
+
+        let big = GameState::new_seeded_with_dimensions(1, 200);
+        assert!(pixels_per_tile(1080, big.max_zoom()) < lod_min_pixels);
+        assert!(pixels_per_tile(1080, game_state::MAX_ZOOM) >= lod_min_pixels);
+    }
+
+    #[test]
+    fn arrows_are_a_shaft_and_two_head_segments() {
+        let [shaft, left, right] = arrow_segments((0.0, 0.0), (10.0, 0.0));
+        assert_eq!(shaft, ((0.0, 0.0), (10.0, 0.0)));
+
+        for (start, end) in [left, right] {
+            assert_eq!(start, (10.0, 0.0));
+            assert!(end.0 < 10.0);
+            assert!((f32::hypot(end.0 - 10.0, end.1) - 2.0).abs() < 1e-4);
+        }
+
+        // Mirrored either side of the shaft.
+        assert!((left.1.0 - right.1.0).abs() < 1e-4);
+        assert!((left.1.1 + right.1.1).abs() < 1e-4);
+        assert!(left.1.1 != 0.0);
+    }
+
+    #[test]
+    fn shadow_cutoff_keeps_the_biggest_shadows_within_budget() {
+        assert_eq!(admitted(&[0.5, 3.0, 1.0, 2.0], 2), vec![3.0, 2.0]);
+        assert_eq!(admitted(&[0.5, 3.0, 0.0, 2.0], 8), vec![0.5, 3.0, 2.0]);
+        assert!(admitted(&[0.5, 3.0], 0).is_empty());
+    }
+
+    #[test]
+    fn shadow_cutoff_admits_ties_only_up_to_the_budget() {
+        let radii = [1.0, 2.0, 1.0, 1.0, 1.0, 1.0];
+        assert_eq!(admitted(&radii, 3), vec![1.0, 2.0, 1.0]);
+    }
+
+    #[test]
+    fn mixed_cell_layers_dirt_under_grass_under_stone() {
+        // Grass along the top, bottom right stony, bottom left half grown in.
+        let layers = ground_cell_layers([1.0, 1.0, 0.5, 0.0], [false, false, false, true]);
+        let pushed = layers.iter().flatten().map(|layer| (layer.tile, layer.z)).collect::<Vec<_>>();
+
+        assert_eq!(pushed, vec![
+            (TileType::Dirt, GROUND_Z_DIRT),
+            (TileType::GrassT, GROUND_Z_GRASS),
+            (TileType::GrassTBL, GROUND_Z_GRASS),
+            (TileType::StoneBR, GROUND_Z_STONE),
+        ]);
+        assert_eq!(layers[2].unwrap().alpha, 0.5);
+        assert!(!layers[3].unwrap().shaded);
+
+        // Higher z draws further back, each layer sits in front of the one pushed before it.
+        assert!(pushed.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+
+        // Fully grassed cells skip the dirt underneath.
+        let full = ground_cell_layers([1.0; 4], [false; 4]);
+        assert!(full[0].is_none());
+        assert_eq!(full[1].unwrap().tile, TileType::Grass);
+    }
+
+    #[test]
+    fn scene_target_is_the_window_scaled() {
+        assert_eq!(scaled_target_size((1600, 900), 0.5), (800, 450));
+        assert_eq!(scaled_target_size((1600, 900), 1.0), (1600, 900));
+        assert_eq!(scaled_target_size((3, 1), 0.1), (1, 1));
+    }
+
+    #[test]
+    fn partly_shaded_ground_is_darker_than_lit_ground() {
+        let strength = 0.5;
+        let lit = ground_shade_brightness(1.0, strength);
+        let partly_shaded = ground_shade_brightness(0.6, strength);
+
+        assert_eq!(lit, 1.0);
+        assert!(partly_shaded < lit);
+        assert!(ground_shade_brightness(0.0, strength) < partly_shaded);
+        // Strength 0 turns the effect off.
+        assert_eq!(ground_shade_brightness(0.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn slot_labels_follow_the_tiles_packing() {
+        use crate::game::{Scenario, ScenarioPlanting, ScenarioTerrain, TreeGrowthStage, WorldPosition};
+        use crate::game::game_state::{NUM_TREES_PER_TILE, SoilType};
+
+        let planting = |x, y, offset| ScenarioPlanting {
+            species: TreeSpecies::Ash,
+            position: WorldPosition { coord: TileCoordinate { x, y }, offset: TileOffset { x: offset, y: offset } },
+            stage: TreeGrowthStage::Sapling,
+        };
+        let scenario = Scenario {
+            terrain: ScenarioTerrain::Uniform(SoilType::Normal),
+            random_trees: false,
+            plantings: vec![planting(1, 1, 0.25), planting(0, 0, 0.5), planting(1, 1, 0.75)],
+            ..Scenario::generated(1, 3)
+        };
+        let state = GameState::from_scenario(&scenario).unwrap();
+
+        let labels = slot_labels(&state, 4);
+        let slots = labels.iter().map(|label| (label.slot_index, label.first_on_tile)).collect::<Vec<_>>();
+        assert_eq!(slots, vec![(4 * NUM_TREES_PER_TILE, true), (4 * NUM_TREES_PER_TILE + 1, false)]);
+        assert_eq!(labels[0].pos, (1.25, 1.25));
+
+        assert!(slot_labels(&state, 8).is_empty());
+    }
+
+    #[test]
+    fn ground_uvs_are_inset_within_the_sprite() {
+        let grid_extent = 10.0;
+        let inset = (0.5 / 32.0, 0.5 / 32.0);
+
+        let (min, max) = ground_cell_uvs((3.5, 4.5), grid_extent, inset);
+        assert_eq!((min, max), (inset, (1.0 - inset.0, 1.0 - inset.1)));
+
+        // The left apron shows the right half of the sprite, still inset on its outer edge.
+        let (min, max) = ground_cell_uvs((0.0, 4.5), grid_extent, inset);
+        assert_eq!((min, max), ((0.5, inset.1), (1.0 - inset.0, 1.0 - inset.1)));
+
+        assert_eq!(ground_cell_uvs((3.5, 4.5), grid_extent, (0.0, 0.0)), ((0.0, 0.0), (1.0, 1.0)));
+    }
+
+    #[test]
+    fn flipped_trees_mirror_their_u_coordinates() {
+        let base = (4.0, 2.0);
+        let upright = entity_quads(&[tree_sprite(base, 50.0, 2.0)], usize::MAX);
+        let flipped = entity_quads(&[TreeSprite { flipped: true, ..tree_sprite(base, 50.0, 2.0) }], usize::MAX);
+
+        assert_eq!((upright[1].uv_min.0, upright[1].uv_max.0), (0.0, 1.0));
+        assert_eq!((flipped[1].uv_min.0, flipped[1].uv_max.0), (1.0, 0.0));
+        assert_eq!((upright[1].uv_min.1, upright[1].uv_max.1), (flipped[1].uv_min.1, flipped[1].uv_max.1));
+
+        // Shadows aren't mirrored.
+        assert_eq!((flipped[0].uv_min, flipped[0].uv_max), (upright[0].uv_min, upright[0].uv_max));
+        assert_eq!(flipped[0].pos, upright[0].pos);
+    }
+
+    #[test]
+    fn grid_lines_are_the_requested_pixels_thick() {
+        // 900 pixel tall window showing 9 world units.
+        let pixels_per_unit = 100.0;
+        let quads = grid_line_quads(4, 2.0, pixels_per_unit);
+        assert_eq!(quads.len(), 10);
+
+        let (vertical, horizontal) = (&quads[2], &quads[3]);
+        assert!((vertical.dim.0 - 0.02).abs() < 1e-6);
+        assert!((horizontal.dim.1 - 0.02).abs() < 1e-6);
+        // Centered on the line at x = 1.
+        assert!((vertical.pos.0 + vertical.dim.0 * 0.5 - 1.0).abs() < 1e-6);
+
+        // Zoomed out 4x the same pixel width covers 4x the world.
+        assert!((grid_line_quads(4, 2.0, pixels_per_unit / 4.0)[0].dim.0 - 0.08).abs() < 1e-6);
+    }
+
+    #[test]
+    fn dead_wood_sways_less_and_shadows_stay_put() {
+        assert_eq!(tree_sway(TreeGrowthStage::Mature), 1.0);
+        assert!(tree_sway(TreeGrowthStage::Snag) < tree_sway(TreeGrowthStage::Decline));
+        assert_eq!(tree_sway(TreeGrowthStage::Stump), 0.0);
+
+        let snag = TreeSprite { sway: tree_sway(TreeGrowthStage::Snag), ..tree_sprite((4.0, 2.0), 50.0, 1.0) };
+        let quads = entity_quads(&[snag], usize::MAX);
+        assert_eq!((quads[0].sway, quads[1].sway), (0.0, 0.25));
+    }
+
+    #[test]
+    fn surface_starts_with_the_preferred_format_at_window_size() {
+        let config = initial_surface_config(wgpu::TextureFormat::Bgra8UnormSrgb, winit::dpi::PhysicalSize::new(1280, 720));
+
+        assert_eq!(config.format, wgpu::TextureFormat::Bgra8UnormSrgb);
+        assert_eq!((config.width, config.height), (1280, 720));
+        assert_eq!(config.present_mode, wgpu::PresentMode::Mailbox);
+    }
+
+    #[test]
+    fn origin_axes_mark_the_origin_with_x_and_y_arrows() {
+        use game_state::TILE_DIM;
+
+        let lines = origin_axes_lines();
+        assert_eq!(lines.len(), 2 + 3 + 3);
+
+        // The cross's strokes meet at the origin.
+        for line in &lines[..2] {
+            assert_eq!(((line.from.0 + line.to.0) * 0.5, (line.from.1 + line.to.1) * 0.5), (0.0, 0.0));
+        }
+
+        let x_axis = &lines[2];
+        assert_eq!((x_axis.from, x_axis.to), ((0.0, 0.0), (TILE_DIM * 2.0, 0.0)));
+        assert!(x_axis.color.0 > x_axis.color.1);
+
+        let y_axis = &lines[5];
+        assert_eq!((y_axis.from, y_axis.to), ((0.0, 0.0), (0.0, TILE_DIM * 2.0)));
+        assert!(y_axis.color.1 > y_axis.color.0);
+    }
+
+    #[test]
+    fn two_tree_scene_paints_back_shadow_back_tree_front_shadow_front_tree() {
+        let front = tree_sprite((2.0, 1.0), 40.0, 1.5);
+        let back = TreeSprite { tex_index: 4, ..tree_sprite((2.2, 1.3), 60.0, 2.0) };
+
+        // Handed over front first, painted back to front. The front tree's trunk goes down over the back tree's shadow.
+        let quads = entity_quads(&[front, back], usize::MAX);
+        let order = quads.iter().map(|quad| (quad.tex_index, quad.pos.2)).collect::<Vec<_>>();
+        assert_eq!(order, [(SHADOW_TEX_INDEX, 60.0), (4, 60.0), (SHADOW_TEX_INDEX, 40.0), (3, 40.0)]);
+    }
+}
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use log::warn;
 
-use super::texture::Texture;
+use super::texture::{SamplerConfig, Texture};
 
 // SAFETY:
 //  All values returned from Into<SpriteId> MUST be >= 0 and < std::mem::variant_count::<T>()
@@ -29,6 +30,8 @@ where
 {
     pub id: T,
     pub pos: (usize, usize),
+    // Semantic origin of the sprite (e.g. base of a tree trunk), normalized within the tile from the bottom left.
+    pub anchor: (f32, f32),
 }
 
 pub struct SpriteSheet<T>
@@ -36,7 +39,7 @@ where
     T: SpriteSetIdentifier,
     [(); std::mem::variant_count::<T>()]: Sized,
 {
-    index_map: [usize; std::mem::variant_count::<T>()],
+    index: SpriteIndex<T>,
     pub texture: Texture,
 }
 
@@ -49,9 +52,8 @@ where
         let mut sprite_sheet = image::load_from_memory(bytes)?;
 
         let mut images = Vec::new();
-        let mut index_map = [0; std::mem::variant_count::<T>()];
 
-        for (index, entry) in layout.entries.iter().enumerate() {
+        for entry in layout.entries.iter() {
             let min_x = (entry.pos.0 * layout.tile_dim.0) as u32;
             let min_y = (entry.pos.1 * layout.tile_dim.1) as u32;
             let dim_x = layout.tile_dim.0 as u32;
@@ -59,17 +61,167 @@ where
 
             let sprite = sprite_sheet.crop(min_x, min_y, dim_x, dim_y);
             images.push(sprite);
+        }
+
+        let index = SpriteIndex::new(layout);
+
+        let texture = Texture::try_create_array_texture_from_images(&images, SamplerConfig::PixelArtSmoothMinify, device, queue)?;
+        Ok(SpriteSheet { index, texture })
+    }
+
+    /// Re-map identifiers and anchors from an edited layout without touching the texture.
+    /// Only sprites already in the texture can be referenced: ids may be reassigned, reordered or dropped, but an entry
+    /// at a position that wasn't loaded (or a different tile_dim) needs the sheet to be reloaded and is an error.
+    pub fn reindex(&mut self, layout: &SpriteSheetLayout<T>) -> Result<()> {
+        self.index.reindex(layout)
+    }
+
+    pub fn get_texture_index(&self, identifier: T) -> usize {
+        self.index.get_texture_index(identifier)
+    }
+
+    /// Half a texel in uv space, insetting uvs by this keeps filtering from sampling past the sprite's edge.
+    pub fn half_texel_uv(&self) -> (f32, f32) {
+        self.index.half_texel_uv()
+    }
+
+    pub fn get_anchor(&self, identifier: T) -> (f32, f32) {
+        self.index.get_anchor(identifier)
+    }
+}
+
+/// Texture layer and anchor for each id, everything in a SpriteSheet but the texture itself.
+struct SpriteIndex<T>
+where
+    T: SpriteSetIdentifier,
+    [(); std::mem::variant_count::<T>()]: Sized,
+{
+    index_map: [usize; std::mem::variant_count::<T>()],
+    anchor_map: [(f32, f32); std::mem::variant_count::<T>()],
+    // Sheet position each texture layer was cropped from, layers are cropped in entry order.
+    layer_positions: Vec<(usize, usize)>,
+    tile_dim: (usize, usize),
+}
+
+impl<T> SpriteIndex<T>
+where
+    T: SpriteSetIdentifier,
+    [(); std::mem::variant_count::<T>()]: Sized,
+{
+    fn new(layout: &SpriteSheetLayout<T>) -> Self {
+        let mut result = Self {
+            index_map: [0; std::mem::variant_count::<T>()],
+            anchor_map: [(0.0, 0.0); std::mem::variant_count::<T>()],
+            layer_positions: layout.entries.iter().map(|entry| entry.pos).collect(),
+            tile_dim: layout.tile_dim,
+        };
+
+        // Every entry's position was just loaded, this can't fail.
+        result.reindex(layout).unwrap();
+        result
+    }
+
+    fn reindex(&mut self, layout: &SpriteSheetLayout<T>) -> Result<()> {
+        if layout.tile_dim != self.tile_dim {
+            bail!("Cannot reindex sprite sheet, tile_dim changed {:?} -> {:?}.", self.tile_dim, layout.tile_dim);
+        }
+
+        let mut index_map = [0; std::mem::variant_count::<T>()];
+        let mut anchor_map = [(0.0, 0.0); std::mem::variant_count::<T>()];
+
+        for entry in layout.entries.iter() {
+            let layer = match self.layer_positions.iter().position(|&pos| pos == entry.pos) {
+                Some(layer) => layer,
+                None => bail!("Cannot reindex sprite sheet, no sprite was loaded from {:?}.", entry.pos),
+            };
 
             let id: SpriteId = entry.id.into();
-            *index_map.get_mut(id.0).unwrap() = index;
+            *index_map.get_mut(id.0).unwrap() = layer;
+            *anchor_map.get_mut(id.0).unwrap() = entry.anchor;
         }
 
-        let texture = Texture::try_create_array_texture_from_images(&images, device, queue)?;
-        Ok(SpriteSheet { index_map, texture })
+        warn_unregistered_ids(layout);
+
+        self.index_map = index_map;
+        self.anchor_map = anchor_map;
+
+        Ok(())
     }
 
-    pub fn get_texture_index(&self, identifier: T) -> usize {
+    fn get_texture_index(&self, identifier: T) -> usize {
         let id: SpriteId = identifier.into();
         *self.index_map.get(id.0).unwrap_or(&0)
     }
+
+    fn half_texel_uv(&self) -> (f32, f32) {
+        (0.5 / self.tile_dim.0 as f32, 0.5 / self.tile_dim.1 as f32)
+    }
+
+    fn get_anchor(&self, identifier: T) -> (f32, f32) {
+        let id: SpriteId = identifier.into();
+        *self.anchor_map.get(id.0).unwrap_or(&(0.0, 0.0))
+    }
+}
+
+// Ids without an entry silently draw layer 0, which is easy to miss when adding new sprites.
+fn warn_unregistered_ids<T>(layout: &SpriteSheetLayout<T>)
+where
+    T: SpriteSetIdentifier,
+    [(); std::mem::variant_count::<T>()]: Sized,
+{
+    let mut registered = [false; std::mem::variant_count::<T>()];
+    for entry in layout.entries.iter() {
+        let id: SpriteId = entry.id.into();
+        registered[id.0] = true;
+    }
+
+    for (id, _) in registered.iter().enumerate().filter(|(_, &r)| !r) {
+        warn!("Sprite sheet {} has no entry for SpriteId({}), it will draw sprite 0.", layout.label, id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy)]
+    enum TestSprite { A, B, C }
+
+    unsafe impl SpriteSetIdentifier for TestSprite {}
+
+    impl From<TestSprite> for SpriteId {
+        fn from(sprite: TestSprite) -> Self { SpriteId(sprite as usize) }
+    }
+
+    fn layout(entries: &[(TestSprite, (usize, usize))]) -> SpriteSheetLayout<TestSprite> {
+        SpriteSheetLayout {
+            label: "test".to_string(),
+            tile_dim: (16, 16),
+            entries: entries.iter().map(|&(id, pos)| SpriteSheetEntry { id, pos, anchor: (0.5, pos.0 as f32 * 0.1) }).collect(),
+        }
+    }
+
+    #[test]
+    fn reindexing_a_reordered_layout_moves_ids_to_the_new_layers() {
+        let mut index = SpriteIndex::new(&layout(&[(TestSprite::A, (0, 0)), (TestSprite::B, (1, 0)), (TestSprite::C, (2, 0))]));
+        assert_eq!([TestSprite::A, TestSprite::B, TestSprite::C].map(|id| index.get_texture_index(id)), [0, 1, 2]);
+
+        // Same sprites, handed out to different ids: C takes A's layer, A takes B's and B takes C's.
+        index.reindex(&layout(&[(TestSprite::C, (0, 0)), (TestSprite::A, (1, 0)), (TestSprite::B, (2, 0))])).unwrap();
+        assert_eq!([TestSprite::A, TestSprite::B, TestSprite::C].map(|id| index.get_texture_index(id)), [1, 2, 0]);
+        assert_eq!(index.get_anchor(TestSprite::C), (0.5, 0.0));
+    }
+
+    #[test]
+    fn reindexing_needs_the_sprites_to_have_been_loaded() {
+        let mut index = SpriteIndex::new(&layout(&[(TestSprite::A, (0, 0)), (TestSprite::B, (1, 0)), (TestSprite::C, (2, 0))]));
+
+        assert!(index.reindex(&layout(&[(TestSprite::A, (3, 0))])).is_err());
+        let mut resized = layout(&[(TestSprite::A, (0, 0))]);
+        resized.tile_dim = (32, 32);
+        assert!(index.reindex(&resized).is_err());
+
+        // Failed reindexes leave the old mapping in place.
+        assert_eq!(index.get_texture_index(TestSprite::B), 1);
+    }
 }
@@ -11,6 +11,46 @@ pub struct Texture {
     pub format: wgpu::TextureFormat,
 }
 
+/// How a texture is filtered when sampled, picked per texture rather than baked into each constructor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SamplerConfig {
+    // Nearest everywhere, texels stay hard edged at any zoom.
+    PixelArt,
+    // Nearest when magnified, filtered when minified so zoomed out sprites don't shimmer.
+    PixelArtSmoothMinify,
+    // Linear everywhere.
+    Smooth,
+}
+
+impl SamplerConfig {
+    /// (mag, min, mipmap)
+    pub fn filters(&self) -> (wgpu::FilterMode, wgpu::FilterMode, wgpu::FilterMode) {
+        use wgpu::FilterMode::*;
+
+        match self {
+            Self::PixelArt             => (Nearest, Nearest, Nearest),
+            Self::PixelArtSmoothMinify => (Nearest, Linear,  Linear),
+            Self::Smooth               => (Linear,  Linear,  Linear),
+        }
+    }
+
+    fn create_sampler(&self, device: &wgpu::Device) -> wgpu::Sampler {
+        let (mag_filter, min_filter, mipmap_filter) = self.filters();
+
+        device.create_sampler(
+            &wgpu::SamplerDescriptor {
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter,
+                min_filter,
+                mipmap_filter,
+                ..Default::default()
+            }
+        )
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum TextureCreationError {
     Error(&'static str),
@@ -31,20 +71,26 @@ impl std::fmt::Display for TextureCreationError {
 impl std::error::Error for TextureCreationError { }
 
 impl Texture {
-    pub fn try_from_path<P: AsRef<Path>>(path: P, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self> {
+    pub fn try_from_path<P: AsRef<Path>>(path: P, sampler: SamplerConfig, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self> {
         let pbuf  = path.as_ref().to_path_buf();
         let label = pbuf.to_str();
         let image = image::open(path)?;
 
-        Self::try_from_image(label, &image, device, queue)
+        Self::try_from_image(label, &image, sampler, device, queue)
     }
 
-    pub fn try_from_bytes(label: Option<&str>, bytes: &[u8], device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self> {
+    pub fn try_from_bytes(label: Option<&str>, bytes: &[u8], sampler: SamplerConfig, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self> {
         let image = image::load_from_memory(bytes)?;
-        Self::try_from_image(label, &image, device, queue)
+        Self::try_from_image(label, &image, sampler, device, queue)
     }
 
-    pub fn try_from_image(label: Option<&str>, image: &image::DynamicImage, device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self> {
+    pub fn try_from_image(
+        label: Option<&str>,
+        image: &image::DynamicImage,
+        sampler: SamplerConfig,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue
+    ) -> Result<Self> {
         let image_rgba = image.to_rgba8();
         let (x_dim, y_dim) = image.dimensions();
 
@@ -85,22 +131,17 @@ impl Texture {
         );
 
         let view = device_texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let sampler = device.create_sampler(
-            &wgpu::SamplerDescriptor {
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Nearest,
-                min_filter: wgpu::FilterMode::Nearest,
-                mipmap_filter: wgpu::FilterMode::Nearest,
-                ..Default::default()
-            }
-        );
+        let sampler = sampler.create_sampler(device);
 
         Ok(Self { device_texture, view, sampler, format })
     }
 
-    pub fn try_create_array_texture_from_images(images: &[image::DynamicImage], device: &wgpu::Device, queue: &wgpu::Queue) -> Result<Self> {
+    pub fn try_create_array_texture_from_images(
+        images: &[image::DynamicImage],
+        sampler: SamplerConfig,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue
+    ) -> Result<Self> {
         use TextureCreationError::*;
 
         debug!("loading array texture");
@@ -174,17 +215,7 @@ impl Texture {
                 ..Default::default()
             }
         );
-        let sampler = device.create_sampler(
-            &wgpu::SamplerDescriptor {
-                address_mode_u: wgpu::AddressMode::ClampToEdge,
-                address_mode_v: wgpu::AddressMode::ClampToEdge,
-                address_mode_w: wgpu::AddressMode::ClampToEdge,
-                mag_filter: wgpu::FilterMode::Nearest,
-                min_filter: wgpu::FilterMode::Linear,
-                mipmap_filter: wgpu::FilterMode::Linear,
-                ..Default::default()
-            }
-        );
+        let sampler = sampler.create_sampler(device);
 
         Ok(Self { device_texture, view, sampler, format })
     }
@@ -225,43 +256,41 @@ impl Texture {
         Self { device_texture, view, sampler, format }
     }
 
-    // pub fn create_surface_sized_depth_texture(label: &str, device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> Self {
-    //     let format = wgpu::TextureFormat::Depth32Float;
-    //     let size = wgpu::Extent3d {
-    //         width: config.width,
-    //         height: config.height,
-    //         depth_or_array_layers: 1,
-    //     };
-
-    //     let device_texture = device.create_texture(
-    //         &wgpu::TextureDescriptor {
-    //             label: Some(label),
-    //             size,
-    //             mip_level_count: 1,
-    //             sample_count: 1,
-    //             dimension: wgpu::TextureDimension::D2,
-    //             format,
-    //             usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-    //         }
-    //     );
-
-    //     let view = device_texture.create_view(&wgpu::TextureViewDescriptor::default());
-
-    //     let sampler = device.create_sampler(
-    //         &wgpu::SamplerDescriptor {
-    //             address_mode_u: wgpu::AddressMode::ClampToEdge,
-    //             address_mode_v: wgpu::AddressMode::ClampToEdge,
-    //             address_mode_w: wgpu::AddressMode::ClampToEdge,
-    //             mag_filter: wgpu::FilterMode::Linear,
-    //             min_filter: wgpu::FilterMode::Linear,
-    //             mipmap_filter: wgpu::FilterMode::Nearest,
-    //             lod_min_clamp: -100.0,
-    //             lod_max_clamp: 100.0,
-    //             compare: Some(wgpu::CompareFunction::LessEqual),
-    //             ..Default::default()
-    //         }
-    //     );
-
-    //     Self { device_texture, view, sampler, format }
-    // }
+    /// Color texture that can be rendered into and then sampled, e.g. to upscale a reduced resolution scene.
+    pub fn create_render_target(label: &str, device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Self {
+        let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+
+        let device_texture = device.create_texture(
+            &wgpu::TextureDescriptor {
+                label: Some(label),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                // COPY_SRC for frame captures.
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_SRC,
+            }
+        );
+
+        let view = device_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        // Upscaling a reduced resolution scene, blurring it would defeat the point of pixel art.
+        let sampler = SamplerConfig::PixelArt.create_sampler(device);
+
+        Self { device_texture, view, sampler, format }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presets_pick_their_filters() {
+        use wgpu::FilterMode::*;
+
+        assert_eq!(SamplerConfig::PixelArt.filters(), (Nearest, Nearest, Nearest));
+        assert_eq!(SamplerConfig::PixelArtSmoothMinify.filters(), (Nearest, Linear, Linear));
+        assert_eq!(SamplerConfig::Smooth.filters(), (Linear, Linear, Linear));
+    }
 }
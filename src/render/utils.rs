@@ -11,6 +11,24 @@ pub mod gpu {
         )
     }
 
+    /// wgpu panics on invalid shaders, so anything not baked into the binary gets checked with naga first.
+    /// Pipelines look for a vertex and a fragment entry point both called main.
+    pub fn validate_wgsl(src: &str) -> anyhow::Result<()> {
+        let module = naga::front::wgsl::parse_str(src).map_err(|e| anyhow::anyhow!(e.emit_to_string(src)))?;
+
+        naga::valid::Validator::new(naga::valid::ValidationFlags::all(), naga::valid::Capabilities::empty())
+            .validate(&module)
+            .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+
+        for stage in [naga::ShaderStage::Vertex, naga::ShaderStage::Fragment] {
+            if !module.entry_points.iter().any(|ep| ep.stage == stage && ep.name == "main") {
+                anyhow::bail!("missing {:?} entry point main", stage);
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn create_buffer(device: &wgpu::Device, label: &str, len_bytes: usize, usage: wgpu::BufferUsages) -> wgpu::Buffer {
         debug!("Allocating {}B buffer: {}", len_bytes, label);
 
@@ -44,15 +62,22 @@ pub mod gpu {
         )
     }
 
+    /// What a pipeline draws and how it writes it out, the parts of create_render_pipeline that aren't shader inputs.
+    pub struct PipelineOutput {
+        pub topology: wgpu::PrimitiveTopology,
+        pub color_format: wgpu::TextureFormat,
+        pub blend: Option<wgpu::BlendState>,
+        pub depth_stencil: Option<wgpu::DepthStencilState>,
+    }
+
     pub fn create_render_pipeline(
         device: &wgpu::Device,
         label: &str,
         bind_group_layouts: &[&wgpu::BindGroupLayout],
         push_constant_ranges: &[wgpu::PushConstantRange],
         buffer_layouts: &[wgpu::VertexBufferLayout],
-        fragment_color_format: wgpu::TextureFormat,
         shader_module: &wgpu::ShaderModule,
-        depth_stencil: Option<wgpu::DepthStencilState>
+        output: PipelineOutput,
     ) -> wgpu::RenderPipeline {
         let layout_label = &(String::from(label) + " -> layout");
 
@@ -76,15 +101,15 @@ pub mod gpu {
             entry_point: "main",
             targets: &[
                 wgpu::ColorTargetState {
-                    format: fragment_color_format,
-                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    format: output.color_format,
+                    blend: output.blend,
                     write_mask: wgpu::ColorWrites::ALL,
                 }
             ],
         };
 
         let primitive_state = wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleList,
+            topology: output.topology,
             strip_index_format: None,
             front_face: wgpu::FrontFace::Ccw,
             cull_mode: Some(wgpu::Face::Back),
@@ -101,7 +126,7 @@ pub mod gpu {
                 fragment: Some(fragment_state),
                 primitive: primitive_state,
                 // depth_stencil: None,
-                depth_stencil,
+                depth_stencil: output.depth_stencil,
                 multisample: wgpu::MultisampleState {
                     count: 1,
                     mask: !0,
@@ -111,3 +136,23 @@ pub mod gpu {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::gpu::validate_wgsl;
+
+    #[test]
+    fn bundled_shaders_validate_and_broken_ones_dont() {
+        for src in [
+            include_str!("../../res/shaders/main_shader.wgsl"),
+            include_str!("../../res/shaders/debug_ui_shader.wgsl"),
+            include_str!("../../res/shaders/blit_shader.wgsl"),
+        ] {
+            validate_wgsl(src).unwrap();
+        }
+
+        assert!(validate_wgsl("fn main( {").is_err());
+        // Parses, but there's nothing to draw with.
+        assert!(validate_wgsl("fn helper() -> f32 { return 1.0; }").is_err());
+    }
+}
@@ -4,42 +4,15 @@ pub trait Vertex: Pod + Zeroable {
     fn describe_buffer<'a>() -> wgpu::VertexBufferLayout<'a>;
 }
 
-#[repr(C)]
-#[derive(Copy, Clone, Debug, Pod, Zeroable)]
-pub struct UvVertex {
-    pub position: [f32; 3],
-    pub uv: [f32; 2],
-}
-
-impl Vertex for UvVertex {
-    fn describe_buffer<'a>() -> wgpu::VertexBufferLayout<'a> {
-        use std::mem::size_of;
-
-        wgpu::VertexBufferLayout {
-            array_stride: size_of::<Self>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[
-                wgpu::VertexAttribute {
-                    shader_location: 0,
-                    format: wgpu::VertexFormat::Float32x3,
-                    offset: 0,
-                },
-                wgpu::VertexAttribute {
-                    shader_location: 1,
-                    format: wgpu::VertexFormat::Float32x2,
-                    offset: size_of::<[f32; 3]>() as wgpu::BufferAddress,
-                },
-            ]
-        }
-    }
-}
-
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct TexturedVertex {
     pub position: [f32; 3],
     pub tex_coords: [f32; 2],
     pub tex_index: i32,
+    pub tint: [f32; 4],
+    // How strongly the wind moves this vertex, 0 => planted.
+    pub sway: f32,
 }
 
 impl Vertex for TexturedVertex {
@@ -64,7 +37,17 @@ impl Vertex for TexturedVertex {
                     shader_location: 2,
                     format: wgpu::VertexFormat::Sint32,
                     offset: size_of::<[f32; 5]>() as wgpu::BufferAddress,
-                }
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: (size_of::<[f32; 5]>() + size_of::<i32>()) as wgpu::BufferAddress,
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 4,
+                    format: wgpu::VertexFormat::Float32,
+                    offset: (size_of::<[f32; 9]>() + size_of::<i32>()) as wgpu::BufferAddress,
+                },
             ],
         }
     }
@@ -75,6 +58,9 @@ impl Vertex for TexturedVertex {
 pub struct ColoredVertex {
     pub position: [f32; 3],
     pub color: [f32; 4],
+    // For rounded quads: (offset from quad center, quad half extents). Unused when corner_radius is 0.
+    pub rect: [f32; 4],
+    pub corner_radius: f32,
 }
 
 impl Vertex for ColoredVertex {
@@ -95,6 +81,16 @@ impl Vertex for ColoredVertex {
                     format: wgpu::VertexFormat::Float32x4,
                     offset: size_of::<[f32; 3]>() as wgpu::BufferAddress,
                 },
+                wgpu::VertexAttribute {
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                    offset: size_of::<[f32; 7]>() as wgpu::BufferAddress,
+                },
+                wgpu::VertexAttribute {
+                    shader_location: 3,
+                    format: wgpu::VertexFormat::Float32,
+                    offset: size_of::<[f32; 11]>() as wgpu::BufferAddress,
+                },
             ],
         }
     }
@@ -0,0 +1,95 @@
+//NOTE:
+//  The world camera maps z 0..100 onto depth 0..1, nearer is smaller. Each layer owns a band of that range so
+//  depth tested layers can never interleave, regardless of draw order.
+//
+//    0..10   Overlays (debug lines).
+//   10..90   Entities and their shadows, spread over the grid's height so lower trees sit in front.
+//   96..100  Ground, grid lines above stone above grass above dirt.
+//
+//  The UI is drawn through its own canvas camera (z 0..1) and always sits at UI_Z.
+
+pub const OVERLAY_Z: f32 = 5.0;
+
+pub const ENTITY_Z_MIN: f32 = 10.0;
+pub const ENTITY_Z_MAX: f32 = 90.0;
+
+pub const GROUND_Z_GRID: f32 = 96.0;
+pub const GROUND_Z_STONE: f32 = 97.0;
+pub const GROUND_Z_GRASS: f32 = 98.0;
+pub const GROUND_Z_DIRT: f32 = 99.0;
+
+pub const UI_Z: f32 = 0.0;
+
+/// Depth for an entity standing at world_y, with 0..world_height scaled onto the entity band so any grid size fits.
+pub fn entity_z(world_y: f32, world_height: f32) -> f32 {
+    if world_height <= 0.0 { return ENTITY_Z_MIN; }
+
+    let t = (world_y / world_height).clamp(0.0, 1.0);
+    ENTITY_Z_MIN + (ENTITY_Z_MAX - ENTITY_Z_MIN) * t
+}
+
+/// Like entity_z, but entities within the same y_range wide strip are ordered by canopy size instead, bigger behind.
+/// Stops a sprout a hair in front of a big tree from cutting into its trunk.
+pub fn understory_entity_z(world_y: f32, canopy_radius: f32, y_range: f32, world_height: f32) -> f32 {
+    if y_range <= 0.0 { return entity_z(world_y, world_height); }
+
+    let strip_y = (world_y / y_range).floor() * y_range;
+    let canopy = canopy_radius.max(0.0);
+
+    // Maps 0..inf onto 0..1 so the offset never leaves the strip.
+    entity_z(strip_y + (y_range * (canopy / (canopy + 1.0))), world_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layers_sit_in_their_bands() {
+        let bands = [
+            (OVERLAY_Z, 0.0..ENTITY_Z_MIN),
+            (GROUND_Z_GRID, ENTITY_Z_MAX..100.0),
+            (GROUND_Z_STONE, ENTITY_Z_MAX..100.0),
+            (GROUND_Z_GRASS, ENTITY_Z_MAX..100.0),
+            (GROUND_Z_DIRT, ENTITY_Z_MAX..100.0),
+        ];
+        for (z, band) in bands {
+            assert!(band.contains(&z), "{} outside {:?}", z, band);
+        }
+
+        // Grid lines above stone above grass above dirt.
+        let ground = [GROUND_Z_GRID, GROUND_Z_STONE, GROUND_Z_GRASS, GROUND_Z_DIRT];
+        assert!(ground.windows(2).all(|pair| pair[0] < pair[1]));
+
+        for y in [-5.0, 0.0, 12.5, 30.0, 200.0] {
+            let z = entity_z(y, 30.0);
+            assert!((ENTITY_Z_MIN..=ENTITY_Z_MAX).contains(&z), "{} -> {}", y, z);
+        }
+    }
+
+    #[test]
+    fn entity_z_spreads_large_grids_over_the_band() {
+        // On a 100 tile grid, trees past y = 80 used to all clamp to the same depth.
+        let world_height = 100.0;
+        let zs = [0.0, 50.0, 85.0, 95.0, 100.0].map(|y| entity_z(y, world_height));
+
+        assert_eq!(zs[0], ENTITY_Z_MIN);
+        assert_eq!(zs[4], ENTITY_Z_MAX);
+        assert!(zs.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn understory_puts_the_bigger_canopy_behind() {
+        let world_height = 30.0;
+        // The big tree stands a hair in front of the sprout.
+        let big = understory_entity_z(10.02, 2.0, 0.1, world_height);
+        let sprout = understory_entity_z(10.06, 0.1, 0.1, world_height);
+
+        // Higher z is further back.
+        assert!(big > sprout);
+
+        // Outside the strip plain y order wins again.
+        let far_sprout = understory_entity_z(12.0, 0.1, 0.1, world_height);
+        assert!(far_sprout > big);
+    }
+}
@@ -2,13 +2,22 @@ use std::time::Duration;
 
 use super::{DurationTimer, Timer};
 
+#[derive(Clone)]
 pub struct AverageDurationTimer<const NUM_MEASUREMENTS: usize = 20> {
     duration_timer: DurationTimer,
     measurement_index: usize,
     measurements: Vec<Duration>,
+    // Exact sum of the window in nanoseconds, updating the average incrementally truncated on every measurement.
+    sum_ns: i128,
     average: Duration,
 }
 
+impl<const NUM_MEASUREMENTS: usize> Default for AverageDurationTimer<NUM_MEASUREMENTS> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<const NUM_MEASUREMENTS: usize> AverageDurationTimer<NUM_MEASUREMENTS> {
     pub fn new() -> Self {
         let mut measurements = Vec::new();
@@ -18,12 +27,15 @@ impl<const NUM_MEASUREMENTS: usize> AverageDurationTimer<NUM_MEASUREMENTS> {
             duration_timer: DurationTimer::new(),
             measurement_index: 0,
             measurements,
+            sum_ns: 0,
             average: Duration::default(),
         }
     }
 
     pub fn clear(&mut self) {
         self.measurement_index = 0;
+        self.measurements.iter_mut().for_each(|m| *m = Duration::default());
+        self.sum_ns = 0;
         self.average = Duration::default();
     }
 
@@ -35,23 +47,21 @@ impl<const NUM_MEASUREMENTS: usize> AverageDurationTimer<NUM_MEASUREMENTS> {
         let new_measurement = self.duration_timer.elapsed();
         self.duration_timer.reset();
 
+        self.record(new_measurement);
+        new_measurement
+    }
+
+    fn record(&mut self, new_measurement: Duration) {
         let index = self.measurement_index % self.measurements.len();
         let old_measurement = self.measurements[index];
 
         self.measurements[index] = new_measurement;
         self.measurement_index += 1;
 
-        self.average = {
-            let avg = self.average.as_nanos() as i128;
-            let old = old_measurement.as_nanos() as i128;
-            let new = new_measurement.as_nanos() as i128;
-
-            let new_average = avg - ((old - new) / NUM_MEASUREMENTS as i128);
-
-            Duration::from_nanos(new_average as u64)
-        };
-
-        new_measurement
+        self.sum_ns += new_measurement.as_nanos() as i128 - old_measurement.as_nanos() as i128;
+        // Only divide by the slots filled so far, otherwise early averages are dragged down by the zeroed slots.
+        let populated_count = usize::min(self.measurement_index, NUM_MEASUREMENTS) as i128;
+        self.average = Duration::from_nanos((self.sum_ns / populated_count) as u64);
     }
 
     pub fn begin_measure(&mut self) {
@@ -71,6 +81,34 @@ impl<const NUM_MEASUREMENTS: usize> AverageDurationTimer<NUM_MEASUREMENTS> {
     pub fn measurements(&self) -> &[Duration]{
         &self.measurements
     }
+
+    /// The measurements taken so far, slots past measurement_index are still zeroed until the buffer has wrapped once.
+    pub fn populated(&self) -> &[Duration] {
+        let count = usize::min(self.measurement_index, self.measurements.len());
+        &self.measurements[..count]
+    }
+
+    /// Over the populated window, zero until something has been measured.
+    pub fn min(&self) -> Duration {
+        self.populated().iter().copied().min().unwrap_or_default()
+    }
+
+    /// Over the populated window, zero until something has been measured.
+    pub fn max(&self) -> Duration {
+        self.populated().iter().copied().max().unwrap_or_default()
+    }
+
+    /// Population standard deviation over the populated window.
+    pub fn std_dev(&self) -> Duration {
+        let measurements = self.populated();
+        if measurements.is_empty() { return Duration::default(); }
+
+        let count = measurements.len() as f64;
+        let mean = measurements.iter().map(|m| m.as_secs_f64()).sum::<f64>() / count;
+        let variance = measurements.iter().map(|m| (m.as_secs_f64() - mean).powi(2)).sum::<f64>() / count;
+
+        Duration::from_secs_f64(variance.sqrt())
+    }
 }
 
 impl<const NUM_MEASUREMENTS: usize> Timer for AverageDurationTimer<NUM_MEASUREMENTS> {
@@ -82,3 +120,51 @@ impl<const NUM_MEASUREMENTS: usize> Timer for AverageDurationTimer<NUM_MEASUREME
         self.duration_timer.elapsed()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_max_and_std_dev_skip_unfilled_slots() {
+        let mut timer = AverageDurationTimer::<4>::new();
+        assert_eq!((timer.min(), timer.max(), timer.std_dev()), (Duration::ZERO, Duration::ZERO, Duration::ZERO));
+
+        timer.record(Duration::from_millis(2));
+        timer.record(Duration::from_millis(4));
+        assert_eq!((timer.min(), timer.max()), (Duration::from_millis(2), Duration::from_millis(4)));
+        assert_eq!(timer.std_dev(), Duration::from_millis(1));
+
+        // Wrapped, the oldest measurement falls out of the window.
+        for ms in [6, 8, 10] {
+            timer.record(Duration::from_millis(ms));
+        }
+        assert_eq!((timer.min(), timer.max()), (Duration::from_millis(4), Duration::from_millis(10)));
+    }
+
+    #[test]
+    fn average_matches_the_window_mean_after_many_measurements() {
+        let mut timer = AverageDurationTimer::<20>::new();
+
+        // Odd nanosecond counts that an incremental average truncates on every step.
+        for i in 0..10_000u64 {
+            timer.record(Duration::from_nanos(if i % 2 == 0 { 1_000_003 } else { 7 }));
+        }
+
+        let window = timer.populated();
+        let mean_ns = window.iter().map(|m| m.as_nanos()).sum::<u128>() / window.len() as u128;
+        assert!((timer.average().as_nanos() as i128 - mean_ns as i128).abs() <= 1);
+    }
+
+    #[test]
+    fn average_is_right_before_the_window_fills() {
+        let mut timer = AverageDurationTimer::<600>::new();
+        timer.record(Duration::from_millis(16));
+        assert_eq!(timer.average(), Duration::from_millis(16));
+
+        timer.record(Duration::from_millis(18));
+        assert_eq!(timer.average(), Duration::from_millis(17));
+        assert_eq!(timer.populated(), [Duration::from_millis(16), Duration::from_millis(18)]);
+        assert_eq!(timer.measurements().len(), 600);
+    }
+}
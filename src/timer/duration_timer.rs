@@ -2,10 +2,17 @@ use std::time::{Duration, Instant};
 
 use super::Timer;
 
+#[derive(Clone)]
 pub struct DurationTimer {
     last_instant: Instant,
 }
 
+impl Default for DurationTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DurationTimer {
     pub fn new() -> Self {
         Self {
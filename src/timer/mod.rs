@@ -4,10 +4,12 @@ use std::time::Duration;
 
 mod average_duration_timer;
 mod duration_timer;
+mod peak_tracker;
 mod target_timer;
 
 pub use average_duration_timer::AverageDurationTimer;
 pub use duration_timer::DurationTimer;
+pub use peak_tracker::PeakTracker;
 pub use target_timer::TargetTimer;
 
 pub enum TimerState {
@@ -16,6 +18,7 @@ pub enum TimerState {
 }
 
 // For some reason can't import this directly... need to export it at crate level :|
+#[macro_export]
 macro_rules! measure {
     ($timer:expr, $code:block) => {
         $timer.begin_measure();
@@ -24,7 +27,7 @@ macro_rules! measure {
     }
 }
 
-pub(crate) use measure;
+pub use measure;
 
 pub trait Timer {
     /// Set the start point for measurement to 'now'.
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+/// Remembers the longest duration recorded since the last take, along with whatever explains it.
+#[derive(Clone, Default)]
+pub struct PeakTracker<T> {
+    peak: Option<(Duration, T)>,
+}
+
+impl<T> PeakTracker<T> {
+    pub fn new() -> Self {
+        Self { peak: None }
+    }
+
+    /// Ties keep the earlier record.
+    pub fn record(&mut self, duration: Duration, detail: T) {
+        match &self.peak {
+            Some((peak, _)) if *peak >= duration => {},
+            _ => self.peak = Some((duration, detail)),
+        }
+    }
+
+    pub fn peak(&self) -> Option<&(Duration, T)> {
+        self.peak.as_ref()
+    }
+
+    /// The peak so far, starting a new window.
+    pub fn take(&mut self) -> Option<(Duration, T)> {
+        self.peak.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_the_peak_and_resets_per_window() {
+        let mut tracker = PeakTracker::new();
+        for (ms, frame) in [(16, 0), (40, 1), (12, 2), (40, 3)] {
+            tracker.record(Duration::from_millis(ms), frame);
+        }
+
+        assert_eq!(tracker.take(), Some((Duration::from_millis(40), 1)));
+        assert!(tracker.peak().is_none());
+
+        tracker.record(Duration::from_millis(5), 4);
+        assert_eq!(tracker.take(), Some((Duration::from_millis(5), 4)));
+    }
+}
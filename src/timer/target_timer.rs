@@ -2,6 +2,7 @@ use std::time::Duration;
 
 use super::{DurationTimer, Timer, TimerState};
 
+#[derive(Clone)]
 pub struct TargetTimer {
     target_duration: Duration,
     duration_timer: DurationTimer,